@@ -0,0 +1,149 @@
+//! Derive macro for the vertex types of `wgpu-renderer`, see `geometry::VertexLayout`.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitInt, Result};
+
+#[proc_macro_derive(VertexLayout, attributes(vertex))]
+pub fn derive_vertex_layout(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "vertex types can't be generic",
+        ));
+    }
+    if !has_repr_c(&input)? {
+        return Err(Error::new(
+            Span::call_site(),
+            "vertex types need #[repr(C)] to have a known layout",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "vertex types need named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                Span::call_site(),
+                "vertex types have to be structs",
+            ))
+        }
+    };
+
+    // Locations are assigned at compile time by the renderer, which knows the ones used
+    // by the instances.
+    let mut explicit_locations = Vec::new();
+    let mut attributes = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        explicit_locations.push(match field_location(field)? {
+            Some(location) => quote!(::std::option::Option::Some(#location)),
+            None => quote!(::std::option::Option::None),
+        });
+
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        attributes.push(quote! {
+            wgpu::VertexAttributeDescriptor {
+                offset: ::std::mem::offset_of!(#name, #ident) as wgpu::BufferAddress,
+                shader_location: LOCATIONS[#i],
+                format: <#ty as VertexAttribute>::FORMAT,
+            }
+        });
+    }
+    let types: Vec<_> = fields.iter().map(|field| &field.ty).collect();
+    let nb_fields = fields.len();
+
+    Ok(quote! {
+        const _: () = {
+            use ::wgpu_renderer::__private::{bytemuck, wgpu};
+            use ::wgpu_renderer::geometry::{vertex_locations, VertexAttribute, VertexLayout};
+
+            const LOCATIONS: [u32; #nb_fields] = vertex_locations([#(#explicit_locations),*]);
+
+            // Padding bytes would be uninitialized, which Pod doesn't allow.
+            assert!(
+                ::std::mem::size_of::<#name>() == 0 #(+ ::std::mem::size_of::<#types>())*,
+                "vertex types can't have padding between their fields",
+            );
+
+            #[allow(dead_code)]
+            fn assert_pod<T: bytemuck::Pod>() {}
+            #[allow(dead_code)]
+            fn assert_fields() {
+                #(assert_pod::<#types>();)*
+            }
+
+            unsafe impl bytemuck::Zeroable for #name {}
+            unsafe impl bytemuck::Pod for #name {}
+
+            impl VertexLayout for #name {
+                const BUFFER_DESCRIPTOR: wgpu::VertexBufferDescriptor<'static> =
+                    wgpu::VertexBufferDescriptor {
+                        stride: ::std::mem::size_of::<#name>() as wgpu::BufferAddress,
+                        step_mode: wgpu::InputStepMode::Vertex,
+                        attributes: &[#(#attributes),*],
+                    };
+            }
+        };
+    })
+}
+
+fn has_repr_c(input: &DeriveInput) -> Result<bool> {
+    let mut repr_c = false;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                repr_c = true;
+            }
+            // Skips the value of `align(N)` or `packed(N)`.
+            if meta.input.peek(syn::token::Paren) {
+                let value;
+                syn::parenthesized!(value in meta.input);
+                value.parse::<proc_macro2::TokenStream>()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(repr_c)
+}
+
+/// Location given with `#[vertex(location = N)]`.
+fn field_location(field: &syn::Field) -> Result<Option<u32>> {
+    let mut location = None;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("vertex"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("location") {
+                let value: LitInt = meta.value()?.parse()?;
+                location = Some(value.base10_parse::<u32>()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `location = <shader location>`"))
+            }
+        })?;
+    }
+    Ok(location)
+}
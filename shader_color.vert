@@ -0,0 +1,32 @@
+#version 450
+
+layout(location=0) in vec3 a_pos;
+layout(location=1) in vec2 a_uv;
+
+layout(location=2) in vec4 i_model_0;
+layout(location=3) in vec4 i_model_1;
+layout(location=4) in vec4 i_model_2;
+layout(location=5) in vec4 i_model_3;
+layout(location=6) in vec4 i_color;
+layout(location=7) in vec4 i_uv_rect;
+
+layout(location=10) in vec4 a_color;
+
+layout(location=0) out vec2 v_uv;
+layout(location=1) out vec4 v_color;
+
+layout(set = 1, binding = 0) uniform Model {
+    mat4 u_model;
+};
+
+layout(set = 2, binding = 0) uniform Camera {
+    mat4 u_view_proj;
+};
+
+
+void main() {
+    mat4 i_model = mat4(i_model_0, i_model_1, i_model_2, i_model_3);
+    v_uv = i_uv_rect.xy + a_uv * i_uv_rect.zw;
+    v_color = i_color * a_color;
+    gl_Position = u_view_proj * u_model * i_model * vec4(a_pos, 1.0);
+}
\ No newline at end of file
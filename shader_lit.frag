@@ -0,0 +1,20 @@
+#version 450
+
+layout(location=0) in vec2 v_tex_coords;
+layout(location=1) in vec4 v_color;
+layout(location=2) in vec3 v_normal;
+layout(location=0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform texture2D t_diffuse;
+layout(set = 0, binding = 1) uniform sampler s_diffuse;
+
+// Normalized direction of a light coming from above, in front and to the right.
+const vec3 LIGHT_DIRECTION = vec3(-0.2592, -0.8639, -0.4319);
+const float AMBIENT = 0.2;
+
+void main() {
+    vec3 normal = normalize(v_normal);
+    float diffuse = max(dot(normal, -LIGHT_DIRECTION), 0.0);
+    vec4 color = v_color * texture(sampler2D(t_diffuse, s_diffuse), v_tex_coords);
+    f_color = vec4(color.rgb * min(AMBIENT + diffuse, 1.0), color.a);
+}
\ No newline at end of file
@@ -0,0 +1,15 @@
+#version 450
+
+layout(location=0) out vec3 v_direction;
+
+layout(set = 1, binding = 0) uniform Skybox {
+    mat4 u_inv_view_proj;
+};
+
+// Fullscreen triangle on the far plane, unprojected to get the view direction.
+void main() {
+    vec2 pos = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2) * 2.0 - 1.0;
+    vec4 world = u_inv_view_proj * vec4(pos, 1.0, 1.0);
+    v_direction = world.xyz / world.w;
+    gl_Position = vec4(pos, 1.0, 1.0);
+}
\ No newline at end of file
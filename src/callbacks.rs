@@ -0,0 +1,43 @@
+use crate::error::Error;
+use crate::ressource_manager::RessourceManager;
+use crate::scene::Scene;
+
+use winit::event::WindowEvent;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Hooks called by [`Application::run`](crate::Application::run).
+///
+/// Every method has a default implementation so only the needed ones have to be written,
+/// they do nothing except for [`Callbacks::error`].
+pub trait Callbacks {
+    /// Called once, before the first frame.
+    fn init(&mut self, _ressource_manager: &mut RessourceManager) {}
+
+    /// Called before each frame, `dt` is the time elapsed since the previous call.
+    fn update(&mut self, _ressource_manager: &mut RessourceManager, _dt: Duration) {}
+
+    /// Called before each frame is rendered, after [`Callbacks::update`].
+    /// The draw items submitted to the scene are the ones rendered this frame.
+    fn render(&mut self, _scene: &mut Scene) {}
+
+    /// Called after [`Callbacks::render`], the frame is saved as a PNG to the returned path.
+    /// See [`Renderer::capture_next_frame`](crate::Renderer::capture_next_frame) for what
+    /// capturing a window frame costs.
+    fn screenshot(&mut self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Called for every window event, after the application handled it.
+    fn event(&mut self, _ressource_manager: &mut RessourceManager, _event: &WindowEvent) {}
+
+    /// Called once when the application is about to close.
+    fn exit(&mut self, _ressource_manager: &mut RessourceManager) {}
+
+    /// Called when a frame could not be rendered or saved, the application keeps running.
+    /// Errors are printed to stderr by default.
+    fn error(&mut self, err: Error) {
+        eprintln!("{}", err);
+    }
+}
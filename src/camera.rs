@@ -0,0 +1,199 @@
+use cgmath::{Deg, Matrix4, Point3, SquareMatrix, Vector3};
+
+use std::num::NonZeroU64;
+
+// cgmath projections map the depth to [-1, 1] like OpenGL, wgpu expects [0, 1].
+#[rustfmt::skip]
+const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.0,
+    0.0, 0.0, 0.5, 1.0,
+);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    Perspective {
+        fovy: Deg<f32>,
+        znear: f32,
+        zfar: f32,
+    },
+    /// `height` is the size of the visible area in world units,
+    /// its width is deduced from the aspect ratio.
+    Orthographic { height: f32, znear: f32, zfar: f32 },
+}
+
+/// Point of view used to render the scene.
+///
+/// The aspect ratio of the camera of the [`Renderer`](crate::Renderer) scene is set from
+/// its render target each frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub eye: Point3<f32>,
+    pub target: Point3<f32>,
+    pub up: Vector3<f32>,
+    pub aspect: f32,
+    pub projection: Projection,
+}
+
+impl Default for Camera {
+    /// Orthographic camera looking toward -z and showing the [-1, 1] vertical range,
+    /// so geometry given in normalized coordinates stays on screen.
+    fn default() -> Self {
+        Self {
+            eye: Point3::new(0.0, 0.0, 1.0),
+            target: Point3::new(0.0, 0.0, 0.0),
+            up: Vector3::unit_y(),
+            aspect: 1.0,
+            projection: Projection::Orthographic {
+                height: 2.0,
+                znear: 0.0,
+                zfar: 2.0,
+            },
+        }
+    }
+}
+
+impl Camera {
+    pub fn perspective(eye: Point3<f32>, target: Point3<f32>, fovy: Deg<f32>) -> Self {
+        Self {
+            eye,
+            target,
+            projection: Projection::Perspective {
+                fovy,
+                znear: 0.1,
+                zfar: 100.0,
+            },
+            ..Self::default()
+        }
+    }
+
+    pub fn orthographic(eye: Point3<f32>, target: Point3<f32>, height: f32) -> Self {
+        Self {
+            eye,
+            target,
+            projection: Projection::Orthographic {
+                height,
+                znear: 0.1,
+                zfar: 100.0,
+            },
+            ..Self::default()
+        }
+    }
+
+    /// Updates the aspect ratio for a render target of the given size. Null sizes, like
+    /// the one of a minimized window, leave it unchanged.
+    pub fn set_viewport_size(&mut self, width: u32, height: u32) {
+        if width > 0 && height > 0 {
+            self.aspect = width as f32 / height as f32;
+        }
+    }
+
+    pub fn look_at(&mut self, eye: Point3<f32>, target: Point3<f32>) {
+        self.eye = eye;
+        self.target = target;
+    }
+
+    pub fn view(&self) -> Matrix4<f32> {
+        Matrix4::look_at(self.eye, self.target, self.up)
+    }
+
+    pub fn projection(&self) -> Matrix4<f32> {
+        let projection = match self.projection {
+            Projection::Perspective { fovy, znear, zfar } => {
+                cgmath::perspective(fovy, self.aspect, znear, zfar)
+            }
+            Projection::Orthographic {
+                height,
+                znear,
+                zfar,
+            } => {
+                let half_height = height / 2.0;
+                let half_width = half_height * self.aspect;
+                cgmath::ortho(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    znear,
+                    zfar,
+                )
+            }
+        };
+
+        OPENGL_TO_WGPU_MATRIX * projection
+    }
+
+    pub fn view_projection(&self) -> Matrix4<f32> {
+        self.projection() * self.view()
+    }
+
+    /// Inverse of the view projection without the translation of the camera,
+    /// turns clip space positions into view directions for the skybox.
+    pub fn skybox_matrix(&self) -> Matrix4<f32> {
+        let rotation =
+            Matrix4::look_at_dir(Point3::new(0.0, 0.0, 0.0), self.target - self.eye, self.up);
+        (self.projection() * rotation)
+            .invert()
+            .unwrap_or_else(Matrix4::identity)
+    }
+}
+
+const VIEW_PROJECTION_SIZE: wgpu::BufferAddress =
+    std::mem::size_of::<[f32; 16]>() as wgpu::BufferAddress;
+
+/// Uniform buffer holding the view projection matrix of the camera.
+pub(crate) struct CameraBuffer {
+    pub(crate) bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    buffer: wgpu::Buffer,
+}
+
+impl CameraBuffer {
+    pub(crate) fn new(device: &wgpu::Device) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Camera BindGroupLayout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStage::VERTEX,
+                ty: wgpu::BindingType::UniformBuffer {
+                    dynamic: false,
+                    min_binding_size: NonZeroU64::new(VIEW_PROJECTION_SIZE),
+                },
+                count: None,
+            }],
+        });
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Camera buffer"),
+            size: VIEW_PROJECTION_SIZE,
+            usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Camera BindGroup"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(buffer.slice(..)),
+            }],
+        });
+
+        Self {
+            bind_group_layout,
+            bind_group,
+            buffer,
+        }
+    }
+
+    pub(crate) fn upload(&self, queue: &wgpu::Queue, camera: &Camera) {
+        let view_projection = camera.view_projection();
+        let matrix: &[f32; 16] = view_projection.as_ref();
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(matrix));
+    }
+
+    pub(crate) fn bind<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, idx: u32) {
+        render_pass.set_bind_group(idx, &self.bind_group, &[]);
+    }
+}
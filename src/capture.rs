@@ -0,0 +1,104 @@
+use crate::error::{Error, Result};
+use crate::Renderer;
+
+use winit::event::VirtualKeyCode;
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Dumps every frame to a numbered PNG sequence.
+struct Recording {
+    directory: PathBuf,
+    frame_time: Duration,
+    frame: u32,
+}
+
+/// Screenshots and recording of the frames of an [`Application`](crate::Application).
+#[derive(Default)]
+pub(crate) struct Capture {
+    screenshot_key: Option<(VirtualKeyCode, PathBuf)>,
+    recording: Option<Recording>,
+    next_frame: Option<PathBuf>,
+}
+
+impl Capture {
+    pub(crate) fn set_screenshot_key(&mut self, key: VirtualKeyCode, directory: PathBuf) {
+        self.screenshot_key = Some((key, directory));
+    }
+
+    pub(crate) fn set_recording(&mut self, directory: PathBuf, fps: u32) -> Result<()> {
+        if fps == 0 {
+            return Err(Error::NullFrameRate);
+        }
+        self.recording = Some(Recording {
+            directory,
+            frame_time: Duration::from_secs(1) / fps,
+            frame: 0,
+        });
+        Ok(())
+    }
+
+    pub(crate) fn save_next_frame(&mut self, path: PathBuf) {
+        self.next_frame = Some(path);
+    }
+
+    pub(crate) fn key_pressed(&mut self, key: VirtualKeyCode) {
+        if let Some((screenshot_key, directory)) = &self.screenshot_key {
+            if key == *screenshot_key {
+                self.next_frame = Some(next_screenshot_path(directory));
+            }
+        }
+    }
+
+    /// Recordings are simulated at a fixed frame rate, whatever the time a frame takes.
+    pub(crate) fn frame_time(&self) -> Option<Duration> {
+        self.recording
+            .as_ref()
+            .map(|recording| recording.frame_time)
+    }
+
+    pub(crate) fn before_render(&self, renderer: &mut Renderer) {
+        if self.next_frame.is_some() || self.recording.is_some() {
+            renderer.capture_next_frame();
+        }
+    }
+
+    pub(crate) fn after_render(&mut self, renderer: &mut Renderer) -> Result<()> {
+        let frame = match renderer.take_captured_frame() {
+            Some(frame) => frame,
+            None => return Ok(()),
+        };
+
+        // A failed screenshot doesn't leave a gap in the recording, the first error is reported.
+        let mut result = Ok(());
+        if let Some(path) = self.next_frame.take() {
+            result = save(&frame, &path);
+        }
+        if let Some(recording) = &mut self.recording {
+            let path = recording
+                .directory
+                .join(format!("{:06}.png", recording.frame));
+            recording.frame += 1;
+            result = result.and(save(&frame, &path));
+        }
+        result
+    }
+}
+
+/// First `screenshot_<n>.png` not taken yet in the directory.
+fn next_screenshot_path(directory: &Path) -> PathBuf {
+    (0..)
+        .map(|i| directory.join(format!("screenshot_{:04}.png", i)))
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+fn save(frame: &image::RgbaImage, path: &Path) -> Result<()> {
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)
+            .map_err(|err| Error::ImageEncode(path.into(), image::ImageError::IoError(err)))?;
+    }
+    frame
+        .save(path)
+        .map_err(|err| Error::ImageEncode(path.into(), err))
+}
@@ -0,0 +1,101 @@
+/// Depth (and stencil) configuration of the main render pipeline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthSettings {
+    pub depth_compare: wgpu::CompareFunction,
+    pub depth_write_enabled: bool,
+    /// Value the depth buffer is cleared to at the start of each frame.
+    /// Use `0.0` with a `Greater` compare function (reversed depth).
+    pub clear_depth: f32,
+    /// When set, the depth buffer gets a stencil aspect and uses these operations.
+    pub stencil: Option<StencilSettings>,
+}
+
+impl Default for DepthSettings {
+    fn default() -> Self {
+        Self {
+            depth_compare: wgpu::CompareFunction::Less,
+            depth_write_enabled: true,
+            clear_depth: 1.0,
+            stencil: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StencilSettings {
+    pub state: wgpu::StencilStateDescriptor,
+    /// Reference value used by the `Equal`, `Replace`, ... operations.
+    pub reference: u32,
+    pub clear_stencil: u32,
+}
+
+impl DepthSettings {
+    pub(crate) fn format(&self) -> wgpu::TextureFormat {
+        match self.stencil {
+            Some(_) => wgpu::TextureFormat::Depth24PlusStencil8,
+            None => wgpu::TextureFormat::Depth32Float,
+        }
+    }
+
+    pub(crate) fn state_descriptor(&self) -> wgpu::DepthStencilStateDescriptor {
+        wgpu::DepthStencilStateDescriptor {
+            format: self.format(),
+            depth_write_enabled: self.depth_write_enabled,
+            depth_compare: self.depth_compare,
+            stencil: self
+                .stencil
+                .as_ref()
+                .map(|stencil| stencil.state.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Depth texture matching the size of the render target.
+pub(crate) struct DepthBuffer {
+    pub(crate) view: wgpu::TextureView,
+}
+
+impl DepthBuffer {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        settings: &DepthSettings,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("depth buffer"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: settings.format(),
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Self { view }
+    }
+}
+
+/// Attachment clearing the depth (and stencil) of a view at the start of a pass.
+pub(crate) fn attachment<'a>(
+    view: &'a wgpu::TextureView,
+    settings: &DepthSettings,
+) -> wgpu::RenderPassDepthStencilAttachmentDescriptor<'a> {
+    wgpu::RenderPassDepthStencilAttachmentDescriptor {
+        attachment: view,
+        depth_ops: Some(wgpu::Operations {
+            load: wgpu::LoadOp::Clear(settings.clear_depth),
+            store: true,
+        }),
+        stencil_ops: settings.stencil.as_ref().map(|stencil| wgpu::Operations {
+            load: wgpu::LoadOp::Clear(stencil.clear_stencil),
+            store: true,
+        }),
+    }
+}
@@ -0,0 +1,264 @@
+use crate::ressource_manager::format::TextureFormat;
+
+use std::fmt;
+use std::path::PathBuf;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Kind of resource a handle refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandleKind {
+    Mesh,
+    TextureView,
+    Sampler,
+    Texture,
+    Cubemap,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    BuildWindow(winit::error::OsError),
+    /// No adapter compatible with the surface (or any adapter when headless) was found.
+    AdapterNotFound,
+    RequestDevice(wgpu::RequestDeviceError),
+    /// The swap chain has to be recreated, the frame was skipped.
+    SwapChainLost(wgpu::SwapChainError),
+    ImageDecode(image::ImageError),
+    /// An OpenEXR file is invalid or uses features that are not supported.
+    Exr(&'static str),
+    /// A captured frame could not be saved.
+    ImageEncode(PathBuf, image::ImageError),
+    /// Recordings need at least one frame per second.
+    NullFrameRate,
+    /// The handle was not created by this manager.
+    InvalidHandle {
+        kind: HandleKind,
+        index: u32,
+    },
+    /// The resource the handle refers to has been dropped.
+    StaleHandle {
+        kind: HandleKind,
+        index: u32,
+        generation: u32,
+    },
+    Io(PathBuf, std::io::Error),
+    Obj(tobj::LoadError),
+    Mtl(tobj::LoadError),
+    Gltf(gltf::Error),
+    /// No image was given to create a mipmapped texture.
+    NoMipLevels,
+    /// More pre-built mip levels were given than a full chain has.
+    TooManyMipLevels {
+        count: u32,
+        max: u32,
+    },
+    /// The texture view has no such mip level.
+    MipLevelOutOfRange {
+        level: u32,
+        mip_level_count: u32,
+    },
+    /// A pre-built mip level doesn't have half the size of the previous one.
+    MipLevelSize {
+        level: u32,
+        expected: (u32, u32),
+        found: (u32, u32),
+    },
+    /// The faces of a cubemap have to be square and of the same size.
+    CubemapFaceSize {
+        face: usize,
+        expected: u32,
+        found: (u32, u32),
+    },
+    /// The rectangle to write doesn't fit in the texture.
+    RegionOutOfBounds {
+        origin: (u32, u32),
+        size: (u32, u32),
+        texture_size: (u32, u32),
+    },
+    /// The texel data doesn't match the size of the rectangle and the texture format.
+    TexelDataSize {
+        expected: usize,
+        found: usize,
+    },
+    /// Texels can't be uploaded to textures of a depth format.
+    TextureUploadFormat(TextureFormat),
+    /// A texture could not be copied back to the cpu.
+    Readback(wgpu::BufferAsyncError),
+    /// Color targets can't have a depth format, depth targets need the depth format of
+    /// the renderer.
+    RenderTargetFormat(TextureFormat),
+    /// Render targets can't have more than one mip level.
+    RenderTargetMipLevels(u32),
+    /// The depth target doesn't have the size of the color target.
+    RenderTargetSize {
+        expected: (u32, u32),
+        found: (u32, u32),
+    },
+    /// An atlas needs at least one image and no empty image.
+    EmptyAtlas,
+    /// The vertex type has no attribute at a location the shaders read.
+    MissingVertexAttribute(u32),
+    /// A vertex attribute doesn't have a format the shaders can read it as.
+    VertexAttributeFormat {
+        location: u32,
+        format: wgpu::VertexFormat,
+    },
+    /// A vertex attribute uses a location of the instance attributes.
+    InstanceLocation(u32),
+    /// A glTF primitive has no `POSITION` attribute.
+    MissingPositions {
+        mesh: usize,
+        primitive: usize,
+    },
+    /// Points and lines glTF primitives can't be drawn.
+    UnsupportedPrimitiveMode {
+        mesh: usize,
+        primitive: usize,
+        mode: gltf::mesh::Mode,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::BuildWindow(err) => write!(f, "could not build the window: {}", err),
+            Error::AdapterNotFound => write!(f, "no suitable adapter found"),
+            Error::RequestDevice(err) => write!(f, "device request failed: {}", err),
+            Error::SwapChainLost(err) => write!(f, "swap chain lost: {}", err),
+            Error::ImageDecode(err) => write!(f, "could not decode the image: {}", err),
+            Error::Exr(reason) => write!(f, "could not decode the EXR image: {}", reason),
+            Error::ImageEncode(path, err) => {
+                write!(f, "could not save {}: {}", path.display(), err)
+            }
+            Error::NullFrameRate => write!(f, "cannot record at 0 frames per second"),
+            Error::InvalidHandle { kind, index } => {
+                write!(f, "invalid {:?} handle: {}", kind, index)
+            }
+            Error::StaleHandle {
+                kind,
+                index,
+                generation,
+            } => write!(
+                f,
+                "{:?} handle {}v{} used after being dropped",
+                kind, index, generation
+            ),
+            Error::NoMipLevels => write!(f, "no mip level given"),
+            Error::TooManyMipLevels { count, max } => {
+                write!(f, "{} mip levels given, a full chain has {}", count, max)
+            }
+            Error::MipLevelOutOfRange {
+                level,
+                mip_level_count,
+            } => write!(
+                f,
+                "mip level {} requested from a texture with {} levels",
+                level, mip_level_count
+            ),
+            Error::MipLevelSize {
+                level,
+                expected,
+                found,
+            } => write!(
+                f,
+                "mip level {} is {}x{}, expected {}x{}",
+                level, found.0, found.1, expected.0, expected.1
+            ),
+            Error::CubemapFaceSize {
+                face,
+                expected,
+                found,
+            } => write!(
+                f,
+                "cubemap face {} is {}x{}, expected {}x{}",
+                face, found.0, found.1, expected, expected
+            ),
+            Error::RegionOutOfBounds {
+                origin,
+                size,
+                texture_size,
+            } => write!(
+                f,
+                "region {}x{} at ({}, {}) is out of the {}x{} texture",
+                size.0, size.1, origin.0, origin.1, texture_size.0, texture_size.1
+            ),
+            Error::TexelDataSize { expected, found } => write!(
+                f,
+                "expected {} bytes of texel data, found {}",
+                expected, found
+            ),
+            Error::TextureUploadFormat(format) => {
+                write!(f, "cannot upload texels to a {:?} texture", format)
+            }
+            Error::Readback(err) => write!(f, "texture readback failed: {}", err),
+            Error::RenderTargetFormat(format) => {
+                write!(f, "cannot render into a {:?} texture", format)
+            }
+            Error::RenderTargetMipLevels(mip_level_count) => write!(
+                f,
+                "cannot render into a texture with {} mip levels",
+                mip_level_count
+            ),
+            Error::RenderTargetSize { expected, found } => write!(
+                f,
+                "depth target is {}x{}, expected {}x{}",
+                found.0, found.1, expected.0, expected.1
+            ),
+            Error::EmptyAtlas => write!(f, "cannot pack empty images into an atlas"),
+            Error::MissingVertexAttribute(location) => {
+                write!(f, "vertex type has no attribute at location {}", location)
+            }
+            Error::VertexAttributeFormat { location, format } => write!(
+                f,
+                "vertex attribute at location {} has the {:?} format, unreadable by the shaders",
+                location, format
+            ),
+            Error::InstanceLocation(location) => write!(
+                f,
+                "vertex attribute at location {} overlaps the instance attributes",
+                location
+            ),
+            Error::Io(path, err) => write!(f, "could not read {}: {}", path.display(), err),
+            Error::Obj(err) => write!(f, "could not load the OBJ file: {}", err),
+            Error::Mtl(err) => write!(f, "could not load the MTL file: {}", err),
+            Error::Gltf(err) => write!(f, "could not load the glTF file: {}", err),
+            Error::MissingPositions { mesh, primitive } => write!(
+                f,
+                "primitive {} of glTF mesh {} has no positions",
+                primitive, mesh
+            ),
+            Error::UnsupportedPrimitiveMode {
+                mesh,
+                primitive,
+                mode,
+            } => write!(
+                f,
+                "primitive {} of glTF mesh {} uses the unsupported {:?} mode",
+                primitive, mesh, mode
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::BuildWindow(err) => Some(err),
+            Error::RequestDevice(err) => Some(err),
+            Error::SwapChainLost(err) => Some(err),
+            Error::ImageDecode(err) => Some(err),
+            Error::ImageEncode(_, err) => Some(err),
+            Error::Readback(err) => Some(err),
+            Error::Io(_, err) => Some(err),
+            Error::Obj(err) | Error::Mtl(err) => Some(err),
+            Error::Gltf(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<image::ImageError> for Error {
+    fn from(err: image::ImageError) -> Self {
+        Error::ImageDecode(err)
+    }
+}
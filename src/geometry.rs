@@ -1,33 +0,0 @@
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Vertex {
-    position: [f32; 3],
-    uv: [f32; 2],
-}
-
-impl Vertex {
-    pub const fn new(position: [f32; 3], uv: [f32; 2]) -> Self {
-        Self { position, uv }
-    }
-
-    pub const BUFFER_DESCRIPTOR: wgpu::VertexBufferDescriptor<'static> =
-        wgpu::VertexBufferDescriptor {
-            stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
-            step_mode: wgpu::InputStepMode::Vertex,
-            attributes: &[
-                wgpu::VertexAttributeDescriptor {
-                    offset: 0,
-                    shader_location: 0,
-                    format: wgpu::VertexFormat::Float3,
-                },
-                wgpu::VertexAttributeDescriptor {
-                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                    shader_location: 1,
-                    format: wgpu::VertexFormat::Float2,
-                },
-            ],
-            //&wgpu::vertex_attr_array![0 => Float3, 1 => Float2] waiting for https://github.com/gfx-rs/wgpu/issues/836>
-        };
-}
-
-unsafe impl bytemuck::Pod for Vertex {}
-unsafe impl bytemuck::Zeroable for Vertex {}
@@ -0,0 +1,361 @@
+use crate::error::{Error, Result};
+
+use cgmath::{InnerSpace, Matrix4, SquareMatrix, Vector3, Zero};
+
+use std::ops::Range;
+
+pub mod primitives;
+
+/// Shader locations used by the attributes of [`Instance`], vertices can't use them.
+pub const INSTANCE_LOCATIONS: Range<u32> = 2..8;
+
+/// Shader locations of the vertex attributes, the position and uv are needed by all
+/// the shaders.
+pub const POSITION_LOCATION: u32 = 0;
+pub const UV_LOCATION: u32 = 1;
+/// Meshes with a normal at this location are lit by a fixed directional light,
+/// they need a vertex color too.
+pub const NORMAL_LOCATION: u32 = 8;
+pub const TANGENT_LOCATION: u32 = 9;
+/// Meshes with a vertex color at this location have it multiplied with the texture color.
+pub const COLOR_LOCATION: u32 = 10;
+pub const UV1_LOCATION: u32 = 11;
+
+/// Vertex types usable by the [`MeshManager`](crate::ressource_manager::mesh::MeshManager),
+/// describing the layout of their vertex buffer.
+///
+/// It is best derived. Each field gets the location after the one of the previous
+/// field, skipping the instance locations, unless `#[vertex(location = N)]` is given:
+///
+/// ```
+/// use wgpu_renderer::geometry::{check_vertex_layout, VertexLayout, COLOR_LOCATION};
+///
+/// #[repr(C)]
+/// #[derive(Clone, Copy, VertexLayout)]
+/// struct PackedColorVertex {
+///     position: [f32; 3],
+///     uv: [f32; 2],
+///     #[vertex(location = 10)]
+///     color: [u8; 4],
+/// }
+///
+/// let layout = PackedColorVertex::BUFFER_DESCRIPTOR;
+/// assert_eq!(layout.attributes[2].shader_location, COLOR_LOCATION);
+/// assert!(check_vertex_layout(&layout).is_ok());
+/// ```
+pub trait VertexLayout: bytemuck::Pod + 'static {
+    const BUFFER_DESCRIPTOR: wgpu::VertexBufferDescriptor<'static>;
+}
+
+pub use wgpu_renderer_derive::VertexLayout;
+
+/// Locations of the fields of a derived vertex type. Fields without an explicit location
+/// get the one after the previous field, skipping [`INSTANCE_LOCATIONS`].
+#[doc(hidden)]
+pub const fn vertex_locations<const N: usize>(explicit: [Option<u32>; N]) -> [u32; N] {
+    let mut locations = [0; N];
+    let mut next = 0;
+    let mut i = 0;
+    while i < N {
+        let location = match explicit[i] {
+            Some(location) => {
+                if location >= INSTANCE_LOCATIONS.start && location < INSTANCE_LOCATIONS.end {
+                    panic!("vertex fields can't use the instance locations");
+                }
+                location
+            }
+            None if next >= INSTANCE_LOCATIONS.start && next < INSTANCE_LOCATIONS.end => {
+                INSTANCE_LOCATIONS.end
+            }
+            None => next,
+        };
+        let mut j = 0;
+        while j < i {
+            if locations[j] == location {
+                panic!("two vertex fields have the same location");
+            }
+            j += 1;
+        }
+        locations[i] = location;
+        next = location + 1;
+        i += 1;
+    }
+    locations
+}
+
+/// Formats the built-in shaders can read the attribute at `location` as.
+/// Other locations are not read.
+fn shader_formats(location: u32) -> &'static [wgpu::VertexFormat] {
+    use wgpu::VertexFormat::*;
+    match location {
+        POSITION_LOCATION | NORMAL_LOCATION => &[Float3],
+        UV_LOCATION => &[Float2],
+        COLOR_LOCATION => &[Float4, Uchar4Norm, Ushort4Norm],
+        _ => &[],
+    }
+}
+
+/// Checks that the built-in shaders can draw vertices with this layout: they need a
+/// position and uv, lit vertices need a color, and the formats have to match.
+pub fn check_vertex_layout(layout: &wgpu::VertexBufferDescriptor) -> Result<()> {
+    let find = |location| {
+        layout
+            .attributes
+            .iter()
+            .find(|attribute| attribute.shader_location == location)
+    };
+
+    for attribute in layout.attributes {
+        let location = attribute.shader_location;
+        if INSTANCE_LOCATIONS.contains(&location) {
+            return Err(Error::InstanceLocation(location));
+        }
+        let formats = shader_formats(location);
+        if !formats.is_empty() && !formats.contains(&attribute.format) {
+            return Err(Error::VertexAttributeFormat {
+                location,
+                format: attribute.format,
+            });
+        }
+    }
+
+    let mut needed = vec![POSITION_LOCATION, UV_LOCATION];
+    if find(NORMAL_LOCATION).is_some() {
+        needed.push(COLOR_LOCATION);
+    }
+    match needed
+        .into_iter()
+        .find(|location| find(*location).is_none())
+    {
+        Some(location) => Err(Error::MissingVertexAttribute(location)),
+        None => Ok(()),
+    }
+}
+
+/// Field types of a vertex, with the format of their attribute.
+pub trait VertexAttribute {
+    const FORMAT: wgpu::VertexFormat;
+}
+
+macro_rules! impl_vertex_attribute {
+    ($($ty:ty => $format:ident,)*) => {
+        $(impl VertexAttribute for $ty {
+            const FORMAT: wgpu::VertexFormat = wgpu::VertexFormat::$format;
+        })*
+    };
+}
+
+impl_vertex_attribute! {
+    f32 => Float,
+    [f32; 2] => Float2,
+    [f32; 3] => Float3,
+    [f32; 4] => Float4,
+    u32 => Uint,
+    [u32; 2] => Uint2,
+    [u32; 3] => Uint3,
+    [u32; 4] => Uint4,
+    i32 => Int,
+    [i32; 2] => Int2,
+    [i32; 3] => Int3,
+    [i32; 4] => Int4,
+    [u16; 2] => Ushort2,
+    [u16; 4] => Ushort4,
+}
+
+/// Read as normalized floats, mostly for colors.
+impl VertexAttribute for [u8; 4] {
+    const FORMAT: wgpu::VertexFormat = wgpu::VertexFormat::Uchar4Norm;
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, VertexLayout)]
+pub struct Vertex {
+    position: [f32; 3],
+    uv: [f32; 2],
+}
+
+impl Vertex {
+    pub const fn new(position: [f32; 3], uv: [f32; 2]) -> Self {
+        Self { position, uv }
+    }
+}
+
+/// Vertex with its own color, for vertex-colored rendering.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, VertexLayout)]
+pub struct ColoredVertex {
+    position: [f32; 3],
+    uv: [f32; 2],
+    #[vertex(location = 10)]
+    color: [f32; 4],
+}
+
+impl ColoredVertex {
+    pub const fn new(position: [f32; 3], uv: [f32; 2], color: [f32; 4]) -> Self {
+        Self {
+            position,
+            uv,
+            color,
+        }
+    }
+}
+
+/// Vertex with everything needed for lighting and normal mapping, drawn lit.
+/// Tangents have the handedness of the bitangent in `w`, they and the second uv set
+/// are not read by the built-in shaders.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, VertexLayout)]
+pub struct LitVertex {
+    pub position: [f32; 3],
+    pub uv: [f32; 2],
+    pub normal: [f32; 3],
+    pub tangent: [f32; 4],
+    pub color: [f32; 4],
+    /// Second set of texture coordinates, usually for light maps.
+    pub uv1: [f32; 2],
+}
+
+impl LitVertex {
+    /// White vertex with a tangent along x and the same coordinates in both uv sets.
+    pub const fn new(position: [f32; 3], uv: [f32; 2], normal: [f32; 3]) -> Self {
+        Self {
+            position,
+            uv,
+            normal,
+            tangent: [1.0, 0.0, 0.0, 1.0],
+            color: [1.0; 4],
+            uv1: uv,
+        }
+    }
+}
+
+/// Smooth normals of a triangle list, the average of the normals of the triangles
+/// around each vertex weighted by their area. Unused vertices get a null normal.
+pub fn compute_normals(positions: &[[f32; 3]], indices: &[u32]) -> Vec<[f32; 3]> {
+    let mut normals = vec![Vector3::zero(); positions.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| Vector3::from(positions[triangle[i] as usize]));
+        let normal = (b - a).cross(c - a);
+        for &index in triangle {
+            normals[index as usize] += normal;
+        }
+    }
+
+    normals
+        .into_iter()
+        .map(|normal: Vector3<f32>| {
+            if normal.magnitude2() > 0.0 {
+                normal.normalize().into()
+            } else {
+                [0.0, 0.0, 0.0]
+            }
+        })
+        .collect()
+}
+
+/// Area of a texture, in texture coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvRect {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+impl Default for UvRect {
+    /// The whole texture.
+    fn default() -> Self {
+        Self {
+            min: [0.0, 0.0],
+            max: [1.0, 1.0],
+        }
+    }
+}
+
+impl UvRect {
+    /// Maps coordinates of the whole texture to coordinates inside the rectangle.
+    pub fn map(&self, uv: [f32; 2]) -> [f32; 2] {
+        [
+            self.min[0] + uv[0] * (self.max[0] - self.min[0]),
+            self.min[1] + uv[1] * (self.max[1] - self.min[1]),
+        ]
+    }
+}
+
+/// Per-instance data of an instanced draw, read from a second vertex buffer.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Instance {
+    model: [[f32; 4]; 4],
+    color: [f32; 4],
+    // Offset in xy and scale in zw applied to the texture coordinates.
+    uv_rect: [f32; 4],
+}
+
+impl Default for Instance {
+    fn default() -> Self {
+        Self::new(Matrix4::identity(), [1.0; 4])
+    }
+}
+
+impl Instance {
+    /// `color` is multiplied with the texture color.
+    pub fn new(transform: impl Into<Matrix4<f32>>, color: [f32; 4]) -> Self {
+        Self {
+            model: transform.into().into(),
+            color,
+            uv_rect: [0.0, 0.0, 1.0, 1.0],
+        }
+    }
+
+    /// Only samples an area of the texture, like a sprite of an atlas.
+    pub fn with_uv_rect(mut self, uv_rect: UvRect) -> Self {
+        self.uv_rect = [
+            uv_rect.min[0],
+            uv_rect.min[1],
+            uv_rect.max[0] - uv_rect.min[0],
+            uv_rect.max[1] - uv_rect.min[1],
+        ];
+        self
+    }
+
+    pub const BUFFER_DESCRIPTOR: wgpu::VertexBufferDescriptor<'static> =
+        wgpu::VertexBufferDescriptor {
+            stride: std::mem::size_of::<Instance>() as wgpu::BufferAddress,
+            step_mode: wgpu::InputStepMode::Instance,
+            attributes: &[
+                // A mat4 takes 4 locations, one for each column.
+                wgpu::VertexAttributeDescriptor {
+                    offset: 0,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float4,
+                },
+                wgpu::VertexAttributeDescriptor {
+                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float4,
+                },
+                wgpu::VertexAttributeDescriptor {
+                    offset: std::mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float4,
+                },
+                wgpu::VertexAttributeDescriptor {
+                    offset: std::mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float4,
+                },
+                wgpu::VertexAttributeDescriptor {
+                    offset: std::mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float4,
+                },
+                wgpu::VertexAttributeDescriptor {
+                    offset: std::mem::size_of::<[f32; 20]>() as wgpu::BufferAddress,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float4,
+                },
+            ],
+        };
+}
+
+unsafe impl bytemuck::Pod for Instance {}
+unsafe impl bytemuck::Zeroable for Instance {}
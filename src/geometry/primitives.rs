@@ -0,0 +1,368 @@
+//! Generators of the usual primitive shapes.
+//!
+//! Shapes are centered on the origin and fit in a unit cube unless they take their
+//! dimensions. Texture coordinates go from the top left of the texture when looking at
+//! the outside of the shape, the bitangent `cross(normal, tangent) * w` points toward
+//! the top of the texture like in glTF.
+
+use super::LitVertex;
+
+use cgmath::{InnerSpace, Vector3};
+
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+/// Vertices and indices of a generated shape, ready for
+/// [`MeshManager::create_mesh_indexed`](crate::ressource_manager::mesh::MeshManager::create_mesh_indexed).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshData {
+    pub vertices: Vec<LitVertex>,
+    pub indices: Vec<u32>,
+}
+
+impl MeshData {
+    fn push(&mut self, vertex: LitVertex) -> u32 {
+        self.vertices.push(vertex);
+        self.vertices.len() as u32 - 1
+    }
+
+    /// Adds a grid of `columns` by `rows` quads, `vertex` is given the column and row of
+    /// each vertex. Rows have to go down the texture and columns toward its right, when
+    /// looking at the front of the grid.
+    fn add_grid(&mut self, columns: u32, rows: u32, mut vertex: impl FnMut(u32, u32) -> LitVertex) {
+        let first = self.vertices.len() as u32;
+        for row in 0..=rows {
+            for column in 0..=columns {
+                self.vertices.push(vertex(column, row));
+            }
+        }
+
+        let index = |column: u32, row: u32| first + row * (columns + 1) + column;
+        for row in 0..rows {
+            for column in 0..columns {
+                let top_left = index(column, row);
+                let top_right = index(column + 1, row);
+                let bottom_left = index(column, row + 1);
+                let bottom_right = index(column + 1, row + 1);
+                self.indices.extend_from_slice(&[
+                    top_left,
+                    bottom_left,
+                    bottom_right,
+                    top_left,
+                    bottom_right,
+                    top_right,
+                ]);
+            }
+        }
+    }
+
+    /// Adds a disc of radius 0.5 at height `y`, facing up or down.
+    fn add_cap(&mut self, y: f32, facing_up: bool, sectors: u32) {
+        let (normal, flip) = if facing_up { (1.0, 1.0) } else { (-1.0, -1.0) };
+        // Mapped like a plane seen from its front.
+        let vertex = |x: f32, z: f32| {
+            LitVertex::new([x, y, z], [0.5 + x, 0.5 + flip * z], [0.0, normal, 0.0])
+        };
+
+        let center = self.push(vertex(0.0, 0.0));
+        let first = self.vertices.len() as u32;
+        for sector in 0..=sectors {
+            let (sin, cos) = angle(sector, sectors).sin_cos();
+            self.vertices.push(vertex(0.5 * sin, 0.5 * cos));
+        }
+        for sector in first..first + sectors {
+            if facing_up {
+                self.indices
+                    .extend_from_slice(&[center, sector, sector + 1]);
+            } else {
+                self.indices
+                    .extend_from_slice(&[center, sector + 1, sector]);
+            }
+        }
+    }
+}
+
+/// Angle around the y axis of a sector, starting from the back so the seam of the
+/// texture is hidden from a camera looking toward -z.
+fn angle(sector: u32, sectors: u32) -> f32 {
+    2.0 * PI * (sector as f32 / sectors as f32 - 0.5)
+}
+
+/// Direction of a point of a sphere, `polar` going from the top to the bottom.
+fn sphere_direction(azimuth: f32, polar: f32) -> Vector3<f32> {
+    let (sin_azimuth, cos_azimuth) = azimuth.sin_cos();
+    let (sin_polar, cos_polar) = polar.sin_cos();
+    Vector3::new(sin_polar * sin_azimuth, cos_polar, sin_polar * cos_azimuth)
+}
+
+/// Tangent going around the y axis.
+fn around_y_tangent(azimuth: f32) -> [f32; 4] {
+    let (sin, cos) = azimuth.sin_cos();
+    [cos, 0.0, -sin, 1.0]
+}
+
+fn lit_vertex(
+    position: Vector3<f32>,
+    uv: [f32; 2],
+    normal: Vector3<f32>,
+    tangent: [f32; 4],
+) -> LitVertex {
+    LitVertex {
+        tangent,
+        ..LitVertex::new(position.into(), uv, normal.into())
+    }
+}
+
+/// Square of side 1 in the xz plane facing +y, with `columns` by `rows` quads.
+/// The top of the texture is toward -z.
+pub fn plane(columns: u32, rows: u32) -> MeshData {
+    let (columns, rows) = (columns.max(1), rows.max(1));
+    let mut data = MeshData::default();
+    data.add_grid(columns, rows, |column, row| {
+        let u = column as f32 / columns as f32;
+        let v = row as f32 / rows as f32;
+        LitVertex::new([u - 0.5, 0.0, v - 0.5], [u, v], [0.0, 1.0, 0.0])
+    });
+    data
+}
+
+/// Cube of side 1, each face being split in `subdivisions` by `subdivisions` quads.
+/// Faces don't share their vertices so edges stay sharp.
+pub fn cube(subdivisions: u32) -> MeshData {
+    let subdivisions = subdivisions.max(1);
+    let x = Vector3::unit_x();
+    let y = Vector3::unit_y();
+    let z = Vector3::unit_z();
+    // Normal and up direction of the texture of each face.
+    let faces = [(x, y), (-x, y), (y, -z), (-y, z), (z, y), (-z, y)];
+
+    let mut data = MeshData::default();
+    for (normal, up) in faces {
+        let right = up.cross(normal);
+        data.add_grid(subdivisions, subdivisions, |column, row| {
+            let u = column as f32 / subdivisions as f32;
+            let v = row as f32 / subdivisions as f32;
+            let position = 0.5 * normal + (u - 0.5) * right + (0.5 - v) * up;
+            lit_vertex(position, [u, v], normal, right.extend(1.0).into())
+        });
+    }
+    data
+}
+
+/// Sphere of diameter 1 made of `sectors` slices around the y axis and `stacks`
+/// rings from top to bottom, mapped like an equirectangular panorama.
+pub fn uv_sphere(sectors: u32, stacks: u32) -> MeshData {
+    let (sectors, stacks) = (sectors.max(3), stacks.max(2));
+    let mut data = MeshData::default();
+    data.add_grid(sectors, stacks, |sector, stack| {
+        let azimuth = angle(sector, sectors);
+        let polar = PI * stack as f32 / stacks as f32;
+        let normal = sphere_direction(azimuth, polar);
+        let uv = [sector as f32 / sectors as f32, stack as f32 / stacks as f32];
+        lit_vertex(0.5 * normal, uv, normal, around_y_tangent(azimuth))
+    });
+    data
+}
+
+/// Sphere of diameter 1 made of evenly sized triangles, from an icosahedron whose
+/// triangles are split in four `subdivisions` times.
+pub fn icosphere(subdivisions: u32) -> MeshData {
+    let phi = (1.0 + 5f32.sqrt()) / 2.0;
+    let mut positions: Vec<Vector3<f32>> = [
+        [-1.0, phi, 0.0],
+        [1.0, phi, 0.0],
+        [-1.0, -phi, 0.0],
+        [1.0, -phi, 0.0],
+        [0.0, -1.0, phi],
+        [0.0, 1.0, phi],
+        [0.0, -1.0, -phi],
+        [0.0, 1.0, -phi],
+        [phi, 0.0, -1.0],
+        [phi, 0.0, 1.0],
+        [-phi, 0.0, -1.0],
+        [-phi, 0.0, 1.0],
+    ]
+    .iter()
+    .map(|&position| Vector3::from(position).normalize())
+    .collect();
+    let mut triangles: Vec<[u32; 3]> = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // Edges are shared by two triangles, their middle is only created once.
+        let mut middles = HashMap::new();
+        let mut middle = |a: u32, b: u32| {
+            *middles.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let position = (positions[a as usize] + positions[b as usize]).normalize();
+                positions.push(position);
+                positions.len() as u32 - 1
+            })
+        };
+
+        triangles = triangles
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let (ab, bc, ca) = (middle(a, b), middle(b, c), middle(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let mut data = MeshData::default();
+    for &normal in &positions {
+        let azimuth = normal.x.atan2(normal.z);
+        let uv = [
+            0.5 + azimuth / (2.0 * PI),
+            normal.y.clamp(-1.0, 1.0).acos() / PI,
+        ];
+        data.push(lit_vertex(
+            0.5 * normal,
+            uv,
+            normal,
+            around_y_tangent(azimuth),
+        ));
+    }
+
+    // Triangles crossing the seam at the back would interpolate their texture
+    // coordinates across the whole texture, their vertices on the left of the texture
+    // are duplicated on its right instead.
+    let mut wrapped = HashMap::new();
+    let is_pole = |vertex: &LitVertex| vertex.normal[0] == 0.0 && vertex.normal[2] == 0.0;
+    for triangle in &mut triangles {
+        let vertices = triangle.map(|index| data.vertices[index as usize]);
+        let us = vertices.map(|vertex| vertex.uv[0]);
+        let poles = vertices.map(|vertex| is_pole(&vertex));
+        let seam_us = || (0..3).filter(|&i| !poles[i]).map(|i| us[i]);
+        let min = seam_us().fold(f32::INFINITY, f32::min);
+        let max = seam_us().fold(f32::NEG_INFINITY, f32::max);
+        if max - min > 0.5 {
+            for i in (0..3).filter(|&i| !poles[i] && us[i] < 0.5) {
+                let index = triangle[i];
+                triangle[i] = *wrapped.entry(index).or_insert_with(|| {
+                    let mut vertex = data.vertices[index as usize];
+                    vertex.uv[0] += 1.0;
+                    vertex.uv1 = vertex.uv;
+                    data.push(vertex)
+                });
+            }
+        }
+
+        // Poles have no azimuth, each triangle gets its own one between its other vertices.
+        if let Some(pole) = (0..3).find(|&i| poles[i]) {
+            let u = (0..3)
+                .filter(|&i| i != pole)
+                .map(|i| data.vertices[triangle[i] as usize].uv[0])
+                .sum::<f32>()
+                / 2.0;
+            let mut vertex = vertices[pole];
+            vertex.uv[0] = u;
+            vertex.uv1 = vertex.uv;
+            vertex.tangent = around_y_tangent(2.0 * PI * (u - 0.5));
+            triangle[pole] = data.push(vertex);
+        }
+        data.indices.extend_from_slice(triangle);
+    }
+    data
+}
+
+/// Cylinder of diameter 1 and height 1 along the y axis, with `sectors` slices around
+/// it and its side split in `stacks` rings. Caps are mapped like [`plane`].
+pub fn cylinder(sectors: u32, stacks: u32) -> MeshData {
+    let (sectors, stacks) = (sectors.max(3), stacks.max(1));
+    let mut data = MeshData::default();
+    data.add_grid(sectors, stacks, |sector, stack| {
+        let azimuth = angle(sector, sectors);
+        let v = stack as f32 / stacks as f32;
+        let normal = sphere_direction(azimuth, PI / 2.0);
+        let position = 0.5 * normal + Vector3::new(0.0, 0.5 - v, 0.0);
+        let uv = [sector as f32 / sectors as f32, v];
+        lit_vertex(position, uv, normal, around_y_tangent(azimuth))
+    });
+    data.add_cap(0.5, true, sectors);
+    data.add_cap(-0.5, false, sectors);
+    data
+}
+
+/// Cone of diameter 1 and height 1 pointing toward +y, with `sectors` slices around
+/// it and its side split in `stacks` rings.
+pub fn cone(sectors: u32, stacks: u32) -> MeshData {
+    let (sectors, stacks) = (sectors.max(3), stacks.max(1));
+    let mut data = MeshData::default();
+    data.add_grid(sectors, stacks, |sector, stack| {
+        let azimuth = angle(sector, sectors);
+        let v = stack as f32 / stacks as f32;
+        let around = sphere_direction(azimuth, PI / 2.0);
+        let position = 0.5 * v * around + Vector3::new(0.0, 0.5 - v, 0.0);
+        // The side goes out by half a unit for a unit of height.
+        let normal = (around + Vector3::new(0.0, 0.5, 0.0)).normalize();
+        let uv = [sector as f32 / sectors as f32, v];
+        lit_vertex(position, uv, normal, around_y_tangent(azimuth))
+    });
+    data.add_cap(-0.5, false, sectors);
+    data
+}
+
+/// Cylinder ended by two half spheres along the y axis, `height` includes the half
+/// spheres and is at least the diameter. Each half sphere has `rings` rings.
+pub fn capsule(radius: f32, height: f32, sectors: u32, rings: u32) -> MeshData {
+    let (sectors, rings) = (sectors.max(3), rings.max(1));
+    let half_length = (height / 2.0 - radius).max(0.0);
+    let total_height = 2.0 * (half_length + radius);
+
+    let mut data = MeshData::default();
+    // The straight part is the band between the two rings at the equator.
+    data.add_grid(sectors, 2 * rings + 1, |sector, ring| {
+        let azimuth = angle(sector, sectors);
+        let (polar, center) = if ring <= rings {
+            (PI / 2.0 * ring as f32 / rings as f32, half_length)
+        } else {
+            (PI / 2.0 * (ring - 1) as f32 / rings as f32, -half_length)
+        };
+        let normal = sphere_direction(azimuth, polar);
+        let position = radius * normal + Vector3::new(0.0, center, 0.0);
+        let uv = [
+            sector as f32 / sectors as f32,
+            0.5 - position.y / total_height,
+        ];
+        lit_vertex(position, uv, normal, around_y_tangent(azimuth))
+    });
+    data
+}
+
+/// Ring around the y axis, `major_radius` away from its center and `minor_radius`
+/// thick, with `sectors` slices around the ring and `sides` around its tube.
+pub fn torus(major_radius: f32, minor_radius: f32, sectors: u32, sides: u32) -> MeshData {
+    let (sectors, sides) = (sectors.max(3), sides.max(3));
+    let mut data = MeshData::default();
+    data.add_grid(sectors, sides, |sector, side| {
+        let azimuth = angle(sector, sectors);
+        let v = side as f32 / sides as f32;
+        // The tube is mapped from its top, going outside first.
+        let normal = sphere_direction(azimuth, 2.0 * PI * v);
+        let around = sphere_direction(azimuth, PI / 2.0);
+        let position = major_radius * around + minor_radius * normal;
+        let uv = [sector as f32 / sectors as f32, v];
+        lit_vertex(position, uv, normal, around_y_tangent(azimuth))
+    });
+    data
+}
@@ -1,68 +1,146 @@
+pub mod camera;
 pub mod geometry;
 pub mod ressource_manager;
+pub mod scene;
 
+mod callbacks;
+mod capture;
+mod depth;
+mod error;
+mod readback;
 mod renderer;
+mod skybox;
 
-pub use geometry::Vertex;
+pub use callbacks::Callbacks;
+pub use depth::{DepthSettings, StencilSettings};
+pub use error::{Error, HandleKind, Result};
+pub use geometry::{ColoredVertex, Instance, LitVertex, UvRect, Vertex, VertexLayout};
+pub use renderer::Renderer;
 
-use renderer::Renderer;
+use capture::Capture;
+pub use winit::event;
+
+// Lets the code generated by the derive macros name this crate from inside it too.
+extern crate self as wgpu_renderer;
+
+/// Used by the code generated by the derive macros.
+#[doc(hidden)]
+pub mod __private {
+    pub use bytemuck;
+    pub use wgpu;
+}
 
 pub use winit::window::WindowBuilder;
 use winit::{
-    event::{Event, WindowEvent},
+    event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
     window::Window,
 };
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum ApplicationCreationError {
-    BuildWindowError,
-}
+use std::path::PathBuf;
+use std::time::Instant;
 
 #[allow(dead_code)]
 pub struct Application {
     event_loop: EventLoop<()>,
     window: Window,
     renderer: Renderer,
+    capture: Capture,
 }
 
 impl Application {
-    pub fn create(window_builder: WindowBuilder) -> Result<Self, ApplicationCreationError> {
+    pub fn create(window_builder: WindowBuilder) -> Result<Self> {
         let event_loop = EventLoop::new();
         let window = window_builder
             .build(&event_loop)
-            .map_err(|_| ApplicationCreationError::BuildWindowError)?;
+            .map_err(Error::BuildWindow)?;
 
-        let renderer = futures::executor::block_on(Renderer::create(&window)).unwrap();
+        let renderer = futures::executor::block_on(Renderer::create(&window))?;
 
         Ok(Self {
             event_loop,
             window,
             renderer,
+            capture: Capture::default(),
         })
     }
 
-    pub fn run(self) -> ! {
+    /// Pressing `key` saves the next frame to `screenshot_<n>.png` in `directory`, see
+    /// [`Renderer::capture_next_frame`] for what capturing a window frame costs.
+    pub fn with_screenshot_key(
+        mut self,
+        key: VirtualKeyCode,
+        directory: impl Into<PathBuf>,
+    ) -> Self {
+        self.capture.set_screenshot_key(key, directory.into());
+        self
+    }
+
+    /// Saves every frame to a numbered PNG in `directory`. The time given to
+    /// [`Callbacks::update`] is fixed to `1 / fps` so the sequence plays at `fps` whatever
+    /// the time spent to render and save each frame, which are captured like with
+    /// [`Renderer::capture_next_frame`].
+    pub fn with_recording(mut self, directory: impl Into<PathBuf>, fps: u32) -> Result<Self> {
+        self.capture.set_recording(directory.into(), fps)?;
+        Ok(self)
+    }
+
+    /// Runs the event loop, the given callbacks are called on each frame and event.
+    pub fn run<C: Callbacks + 'static>(self, mut callbacks: C) -> ! {
         let event_loop = self.event_loop;
         let window = self.window;
         let mut renderer = self.renderer;
+        let mut capture = self.capture;
+
+        callbacks.init(renderer.ressource_manager_mut());
+        let mut last_update = Instant::now();
 
         event_loop.run(move |event, _, control_flow| {
             *control_flow = ControlFlow::Poll;
             match event {
-                Event::WindowEvent { event, .. } => match event {
-                    WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
-                    WindowEvent::Resized(new_size) => renderer.resize(new_size),
-                    WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-                        renderer.resize(*new_inner_size)
+                Event::WindowEvent { event, .. } => {
+                    match &event {
+                        WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                        WindowEvent::Resized(new_size) => renderer.resize(*new_size),
+                        WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                            renderer.resize(**new_inner_size)
+                        }
+                        WindowEvent::KeyboardInput {
+                            input:
+                                KeyboardInput {
+                                    state: ElementState::Pressed,
+                                    virtual_keycode: Some(key),
+                                    ..
+                                },
+                            ..
+                        } => capture.key_pressed(*key),
+                        _ => (),
                     }
-                    _ => (),
-                },
+                    callbacks.event(renderer.ressource_manager_mut(), &event);
+                }
                 Event::RedrawRequested(_) => {
+                    let now = Instant::now();
+                    let dt = capture.frame_time().unwrap_or(now - last_update);
+                    callbacks.update(renderer.ressource_manager_mut(), dt);
+                    last_update = now;
+                    callbacks.render(renderer.scene_mut());
+                    if let Some(path) = callbacks.screenshot() {
+                        capture.save_next_frame(path);
+                    }
+
                     renderer.update();
-                    renderer.render();
+                    capture.before_render(&mut renderer);
+                    match renderer.render() {
+                        // The swap chain has been recreated, the next frame will be fine.
+                        Ok(()) | Err(Error::SwapChainLost(_)) => (),
+                        Err(err) => callbacks.error(err),
+                    }
+                    if let Err(err) = capture.after_render(&mut renderer) {
+                        callbacks.error(err);
+                    }
                 }
                 Event::MainEventsCleared => window.request_redraw(),
+                Event::LoopDestroyed => callbacks.exit(renderer.ressource_manager_mut()),
                 _ => (),
             }
         });
@@ -1,11 +1,78 @@
 //! This file is just for dev purpose only. There is not a stable api now
 //! so I prefer this than adding an example.
 
+use wgpu_renderer::event::VirtualKeyCode;
+use wgpu_renderer::ressource_manager::mesh::MeshId;
+use wgpu_renderer::ressource_manager::texture::TextureId;
+use wgpu_renderer::ressource_manager::RessourceManager;
+use wgpu_renderer::scene::Scene;
 use wgpu_renderer::Application;
+use wgpu_renderer::Callbacks;
+use wgpu_renderer::Vertex;
 use wgpu_renderer::WindowBuilder;
 
+#[rustfmt::skip]
+#[allow(clippy::excessive_precision)]
+const VERTICES: &[Vertex] = &[
+    Vertex::new([-0.0868241, 0.49240386, 0.0], [0.4131759, 0.00759614],),
+    Vertex::new([-0.49513406, 0.06958647, 0.0], [0.0048659444, 0.43041354],), 
+    Vertex::new([-0.21918549, -0.44939706, 0.0], [0.28081453, 0.949397057],),
+    Vertex::new([0.35966998, -0.3473291, 0.0], [0.85967, 0.84732911],), 
+    Vertex::new([0.44147372, 0.2347359, 0.0], [0.9414737, 0.2652641],),
+];
+
+const INDICES: &[u16] = &[0, 1, 4, 1, 2, 4, 2, 3, 4];
+
+#[derive(Default)]
+struct Hello {
+    pentagon: Option<(MeshId, TextureId)>,
+}
+
+impl Callbacks for Hello {
+    fn init(&mut self, ressource_manager: &mut RessourceManager) {
+        let mesh_id = ressource_manager
+            .mesh_manager
+            .create_mesh_indexed(VERTICES, INDICES)
+            .unwrap();
+
+        let texture_bytes = include_bytes!("../happy-tree.png");
+        let texture_view_id = ressource_manager
+            .texture_manager
+            .create_texture_view(texture_bytes)
+            .unwrap();
+
+        let sampler_id =
+            ressource_manager
+                .texture_manager
+                .create_sampler(&wgpu::SamplerDescriptor {
+                    address_mode_u: wgpu::AddressMode::ClampToEdge,
+                    address_mode_v: wgpu::AddressMode::ClampToEdge,
+                    address_mode_w: wgpu::AddressMode::ClampToEdge,
+                    mag_filter: wgpu::FilterMode::Linear,
+                    min_filter: wgpu::FilterMode::Nearest,
+                    mipmap_filter: wgpu::FilterMode::Nearest,
+                    ..Default::default()
+                });
+
+        let texture_id = ressource_manager
+            .texture_manager
+            .create_texture(texture_view_id, sampler_id)
+            .unwrap();
+
+        self.pentagon = Some((mesh_id, texture_id));
+    }
+
+    fn render(&mut self, scene: &mut Scene) {
+        if let Some((mesh_id, texture_id)) = self.pentagon {
+            scene.draw(mesh_id, texture_id);
+        }
+    }
+}
+
 fn main() {
     let builder = WindowBuilder::new().with_title("Hello");
-    let application = Application::create(builder).unwrap();
-    application.run();
+    let application = Application::create(builder)
+        .unwrap()
+        .with_screenshot_key(VirtualKeyCode::F12, "screenshots");
+    application.run(Hello::default());
 }
@@ -0,0 +1,64 @@
+use crate::error::{Error, Result};
+
+/// Copies a mip level of a 2D texture into a buffer and reads it back, `size` being the
+/// size of the level. The texture needs the `COPY_SRC` usage. Rows are returned without padding.
+pub(crate) fn read_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    mip_level: u32,
+    (width, height): (u32, u32),
+    bytes_per_pixel: u32,
+) -> Result<Vec<u8>> {
+    // Each row copied into a buffer has to be aligned on COPY_BYTES_PER_ROW_ALIGNMENT.
+    let unpadded_bytes_per_row = bytes_per_pixel * width;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("readback buffer"),
+        size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("readback command encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        wgpu::TextureCopyView {
+            texture,
+            mip_level,
+            origin: wgpu::Origin3d::ZERO,
+        },
+        wgpu::BufferCopyView {
+            buffer: &buffer,
+            layout: wgpu::TextureDataLayout {
+                offset: 0,
+                bytes_per_row: padded_bytes_per_row,
+                rows_per_image: height,
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth: 1,
+        },
+    );
+    queue.submit(Some(encoder.finish()));
+
+    let buffer_slice = buffer.slice(..);
+    let mapping = buffer_slice.map_async(wgpu::MapMode::Read);
+    device.poll(wgpu::Maintain::Wait);
+    futures::executor::block_on(mapping).map_err(Error::Readback)?;
+
+    let padded = buffer_slice.get_mapped_range();
+    let mut texels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+    for row in padded.chunks(padded_bytes_per_row as usize) {
+        texels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+    }
+    drop(padded);
+    buffer.unmap();
+
+    Ok(texels)
+}
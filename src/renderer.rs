@@ -1,51 +1,318 @@
-use crate::geometry::Vertex;
+use crate::camera::CameraBuffer;
+use crate::depth::{self, DepthBuffer, DepthSettings};
+use crate::error::{Error, Result};
+use crate::geometry::{Instance, COLOR_LOCATION, NORMAL_LOCATION};
+use crate::readback;
+use crate::ressource_manager::mesh::PipelineKey;
+use crate::ressource_manager::texture::TextureViewId;
 use crate::ressource_manager::RessourceManager;
+use crate::scene::{InstanceBuffer, ModelBuffer, Scene, TexturePass};
+use crate::skybox::SkyboxPass;
 use crate::Window;
 
 use winit::dpi::PhysicalSize;
 
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
-#[rustfmt::skip]
-const VERTICES: &[Vertex] = &[
-    Vertex::new([-0.0868241, 0.49240386, 0.0], [0.4131759, 0.00759614],),
-    Vertex::new([-0.49513406, 0.06958647, 0.0], [0.0048659444, 0.43041354],), 
-    Vertex::new([-0.21918549, -0.44939706, 0.0], [0.28081453, 0.949397057],),
-    Vertex::new([0.35966998, -0.3473291, 0.0], [0.85967, 0.84732911],), 
-    Vertex::new([0.44147372, 0.2347359, 0.0], [0.9414737, 0.2652641],),
-];
+/// Where the frames produced by [`Renderer::render`] end up.
+enum RenderTarget {
+    Window {
+        surface: wgpu::Surface,
+        swap_chain: wgpu::SwapChain,
+    },
+    /// Offscreen texture used when there is no window, its content can be read back
+    /// with [`Renderer::read_frame`].
+    Headless {
+        texture: wgpu::Texture,
+        view: wgpu::TextureView,
+    },
+}
+
+/// Render pipelines by vertex type, index format and color format, all are part of the
+/// pipeline state in wgpu. A pipeline is created the first time a mesh needs it.
+struct RenderPipelines {
+    layout: wgpu::PipelineLayout,
+    vs_module: wgpu::ShaderModule,
+    /// Vertex shader for the vertex types having a color.
+    vs_color_module: wgpu::ShaderModule,
+    fs_module: wgpu::ShaderModule,
+    /// Shaders lighting the vertex types having a normal and a color.
+    vs_lit_module: wgpu::ShaderModule,
+    fs_lit_module: wgpu::ShaderModule,
+    pipelines: HashMap<(PipelineKey, wgpu::TextureFormat), wgpu::RenderPipeline>,
+}
+
+impl RenderPipelines {
+    fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        depth_settings: &DepthSettings,
+        key: PipelineKey,
+        color_format: wgpu::TextureFormat,
+        vertex_layout: &wgpu::VertexBufferDescriptor,
+    ) {
+        if self.pipelines.contains_key(&(key, color_format)) {
+            return;
+        }
+
+        let has_location = |location| {
+            vertex_layout
+                .attributes
+                .iter()
+                .any(|attribute| attribute.shader_location == location)
+        };
+        let shaders = if has_location(NORMAL_LOCATION) {
+            (&self.vs_lit_module, &self.fs_lit_module)
+        } else if has_location(COLOR_LOCATION) {
+            (&self.vs_color_module, &self.fs_module)
+        } else {
+            (&self.vs_module, &self.fs_module)
+        };
+        let pipeline = self.create_pipeline(
+            device,
+            shaders,
+            depth_settings,
+            key.1,
+            color_format,
+            vertex_layout,
+        );
+        self.pipelines.insert((key, color_format), pipeline);
+    }
+
+    fn create_pipeline(
+        &self,
+        device: &wgpu::Device,
+        (vs_module, fs_module): (&wgpu::ShaderModule, &wgpu::ShaderModule),
+        depth_settings: &DepthSettings,
+        index_format: wgpu::IndexFormat,
+        color_format: wgpu::TextureFormat,
+        vertex_layout: &wgpu::VertexBufferDescriptor,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("render_pipeline"),
+            layout: Some(&self.layout),
+            vertex_stage: wgpu::ProgrammableStageDescriptor {
+                module: vs_module,
+                entry_point: "main",
+            },
+            fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+                module: fs_module,
+                entry_point: "main",
+            }),
+            rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: wgpu::CullMode::Back,
+                clamp_depth: false,
+                depth_bias: 0,
+                depth_bias_slope_scale: 0.0,
+                depth_bias_clamp: 0.0,
+            }),
+            primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+            color_states: &[wgpu::ColorStateDescriptor {
+                format: color_format,
+                alpha_blend: wgpu::BlendDescriptor::REPLACE,
+                color_blend: wgpu::BlendDescriptor::REPLACE,
+                write_mask: wgpu::ColorWrite::ALL,
+            }],
+            depth_stencil_state: Some(depth_settings.state_descriptor()),
+            vertex_state: wgpu::VertexStateDescriptor {
+                index_format,
+                vertex_buffers: &[vertex_layout.clone(), Instance::BUFFER_DESCRIPTOR],
+            },
+            sample_count: 1,
+            sample_mask: !0,
+            alpha_to_coverage_enabled: true,
+        })
+    }
 
-const INDICES: &[u16] = &[0, 1, 4, 1, 2, 4, 2, 3, 4];
+    /// The pipeline has to be prepared beforehand.
+    fn get(&self, key: PipelineKey, color_format: wgpu::TextureFormat) -> &wgpu::RenderPipeline {
+        &self.pipelines[&(key, color_format)]
+    }
+}
+
+/// What is needed to draw a scene, borrowed from the [`Renderer`] so the scene and
+/// the output can be borrowed from it too.
+struct SceneDraw<'a> {
+    device: &'a wgpu::Device,
+    queue: &'a wgpu::Queue,
+    ressource_manager: &'a RessourceManager,
+    render_pipelines: &'a mut RenderPipelines,
+    depth_settings: &'a DepthSettings,
+    model_buffer: &'a mut ModelBuffer,
+    instance_buffer: &'a mut InstanceBuffer,
+    camera_buffer: &'a CameraBuffer,
+    skybox_pass: &'a mut SkyboxPass,
+}
+
+impl SceneDraw<'_> {
+    /// Buffers are shared between scenes, each one is submitted on its own so the
+    /// uploads of the next scene don't overwrite them.
+    fn draw(
+        self,
+        scene: &Scene,
+        output_view: &wgpu::TextureView,
+        color_format: wgpu::TextureFormat,
+        depth_view: &wgpu::TextureView,
+    ) -> Result<()> {
+        let queue = self.queue;
+        self.model_buffer.upload(self.device, queue, scene);
+        self.instance_buffer.upload(self.device, queue, scene);
+        self.camera_buffer.upload(queue, &scene.camera);
+        self.skybox_pass.upload(queue, &scene.camera);
+        let model_buffer = &*self.model_buffer;
+        let instance_buffer = &*self.instance_buffer;
+
+        let mesh_manager = &self.ressource_manager.mesh_manager;
+        for (item, _) in scene.instanced_items() {
+            let (key, vertex_layout) = mesh_manager.pipeline_key(item.mesh)?;
+            self.render_pipelines.prepare(
+                self.device,
+                self.depth_settings,
+                key,
+                color_format,
+                vertex_layout,
+            );
+        }
+        let render_pipelines = &*self.render_pipelines;
+        if scene.skybox.is_some() {
+            self.skybox_pass
+                .prepare(self.device, color_format, self.depth_settings);
+        }
+        let skybox_pass = &*self.skybox_pass;
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("render_pass command encoder"),
+            });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                    attachment: output_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: Some(depth::attachment(depth_view, self.depth_settings)),
+            });
+
+            let texture_manager = &self.ressource_manager.texture_manager;
+            if let Some(skybox) = scene.skybox {
+                texture_manager.bind_cubemap(&mut render_pass, skybox, 0)?;
+                skybox_pass.draw(&mut render_pass, color_format);
+            }
+
+            if let Some(stencil) = &self.depth_settings.stencil {
+                render_pass.set_stencil_reference(stencil.reference);
+            }
+            self.camera_buffer.bind(&mut render_pass, 2);
+            instance_buffer.bind(&mut render_pass, 1);
+            let mut current_key = None;
+            let mut current_texture = None;
+            for (i, (item, instances)) in scene.instanced_items().enumerate() {
+                let (key, _) = mesh_manager.pipeline_key(item.mesh)?;
+                if current_key != Some(key) {
+                    current_key = Some(key);
+                    render_pass.set_pipeline(render_pipelines.get(key, color_format));
+                }
+                if current_texture != Some(item.texture) {
+                    current_texture = Some(item.texture);
+                    texture_manager.bind_texture(&mut render_pass, item.texture, 0)?;
+                }
+
+                model_buffer.bind(&mut render_pass, i, 1);
+                mesh_manager.draw_mesh(&mut render_pass, item.mesh, instances)?;
+            }
+        }
+
+        queue.submit(Some(encoder.finish()));
+        Ok(())
+    }
+}
 
 pub struct Renderer {
-    surface: wgpu::Surface,
+    target: RenderTarget,
     sc_desc: wgpu::SwapChainDescriptor,
-    swap_chain: wgpu::SwapChain,
-    render_pipeline: wgpu::RenderPipeline,
+    depth_settings: DepthSettings,
+    depth_buffer: DepthBuffer,
+    /// Depth buffers of the render to texture passes, by size.
+    target_depth_buffers: HashMap<(u32, u32), DepthBuffer>,
+    render_pipelines: RenderPipelines,
 
     // Providing thread safety has no use for now but I think it may become handy later.
     device: Arc<Mutex<wgpu::Device>>,
     queue: Arc<Mutex<wgpu::Queue>>,
     ressource_manager: RessourceManager,
-
-    // The followings are for testing purpose only
-    mesh_id: u32,
-    texture_id: u32,
+    scene: Scene,
+    model_buffer: ModelBuffer,
+    instance_buffer: InstanceBuffer,
+    camera_buffer: CameraBuffer,
+    skybox_pass: SkyboxPass,
+    capture_requested: bool,
+    captured_frame: Option<image::RgbaImage>,
 }
 
 impl Renderer {
-    pub async fn create(window: &Window) -> Result<Self, ()> {
+    pub async fn create(window: &Window) -> Result<Self> {
         let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
         let surface = unsafe { instance.create_surface(window) };
+        let (device, queue) = Self::request_device(&instance, Some(&surface)).await?;
+
+        let size = window.inner_size();
+        let sc_desc = wgpu::SwapChainDescriptor {
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+
+        let swap_chain = device.create_swap_chain(&surface, &sc_desc);
+        let target = RenderTarget::Window {
+            surface,
+            swap_chain,
+        };
+
+        Ok(Self::build(device, queue, sc_desc, target))
+    }
+
+    /// Creates a renderer without any window, frames are rendered into an offscreen
+    /// `Rgba8UnormSrgb` texture of the given size.
+    pub async fn create_headless(width: u32, height: u32) -> Result<Self> {
+        let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
+        let (device, queue) = Self::request_device(&instance, None).await?;
+
+        let sc_desc = wgpu::SwapChainDescriptor {
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+
+        let target = Self::create_headless_target(&device, &sc_desc);
+
+        Ok(Self::build(device, queue, sc_desc, target))
+    }
+
+    async fn request_device(
+        instance: &wgpu::Instance,
+        compatible_surface: Option<&wgpu::Surface>,
+    ) -> Result<(wgpu::Device, wgpu::Queue)> {
         let adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions {
                 power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: Some(&surface),
+                compatible_surface,
             })
             .await
-            .unwrap();
+            .ok_or(Error::AdapterNotFound)?;
 
-        let (temp_device, temp_queue) = adapter
+        adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     features: wgpu::Features::empty(),
@@ -55,161 +322,414 @@ impl Renderer {
                 None,
             )
             .await
-            .unwrap();
+            .map_err(Error::RequestDevice)
+    }
 
+    fn create_headless_target(
+        device: &wgpu::Device,
+        sc_desc: &wgpu::SwapChainDescriptor,
+    ) -> RenderTarget {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("headless render target"),
+            size: wgpu::Extent3d {
+                width: sc_desc.width,
+                height: sc_desc.height,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: sc_desc.format,
+            usage: sc_desc.usage,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        RenderTarget::Headless { texture, view }
+    }
+
+    fn build(
+        temp_device: wgpu::Device,
+        temp_queue: wgpu::Queue,
+        sc_desc: wgpu::SwapChainDescriptor,
+        target: RenderTarget,
+    ) -> Self {
         let device = Arc::new(Mutex::new(temp_device));
         let queue = Arc::new(Mutex::new(temp_queue));
 
-        let mut ressource_manager = RessourceManager::new(device.clone(), queue.clone());
-
-        let mesh_id = ressource_manager
-            .mesh_manager
-            .create_mesh_indexed(VERTICES, INDICES);
-
-        let texture_bytes = include_bytes!("../happy-tree.png");
-        let texture_view_id = ressource_manager
-            .texture_manager
-            .create_texture_view(texture_bytes);
-
-        let sampler_id =
-            ressource_manager
-                .texture_manager
-                .create_sampler(&wgpu::SamplerDescriptor {
-                    address_mode_u: wgpu::AddressMode::ClampToEdge,
-                    address_mode_v: wgpu::AddressMode::ClampToEdge,
-                    address_mode_w: wgpu::AddressMode::ClampToEdge,
-                    mag_filter: wgpu::FilterMode::Linear,
-                    min_filter: wgpu::FilterMode::Nearest,
-                    mipmap_filter: wgpu::FilterMode::Nearest,
-                    ..Default::default()
-                });
-
-        let texture_id = ressource_manager
-            .texture_manager
-            .create_texture(texture_view_id, sampler_id);
+        let ressource_manager = RessourceManager::new(device.clone(), queue.clone());
 
         let lock_device = device.lock().unwrap();
         let lock_queue = queue.lock().unwrap();
 
-        let size = window.inner_size();
-        let sc_desc = wgpu::SwapChainDescriptor {
-            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
-            width: size.width,
-            height: size.height,
-            present_mode: wgpu::PresentMode::Fifo,
-        };
-
-        let swap_chain = lock_device.create_swap_chain(&surface, &sc_desc);
+        let model_buffer = ModelBuffer::new(&lock_device);
+        let instance_buffer = InstanceBuffer::new(&lock_device);
+        let camera_buffer = CameraBuffer::new(&lock_device);
 
         let render_pipeline_layout =
             lock_device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("render_pipeline_layout"),
-                bind_group_layouts: &[&ressource_manager.texture_manager.bind_group_layout],
+                bind_group_layouts: &[
+                    &ressource_manager.texture_manager.bind_group_layout,
+                    &model_buffer.bind_group_layout,
+                    &camera_buffer.bind_group_layout,
+                ],
                 push_constant_ranges: &[],
             });
 
-        let vs_module =
-            lock_device.create_shader_module(wgpu::include_spirv!("../shader.vert.spv"));
-        let fs_module =
-            lock_device.create_shader_module(wgpu::include_spirv!("../shader.frag.spv"));
-        let render_pipeline = lock_device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("render_pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex_stage: wgpu::ProgrammableStageDescriptor {
-                module: &vs_module,
-                entry_point: "main",
-            },
-            fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
-                module: &fs_module,
-                entry_point: "main",
-            }),
-            rasterization_state: Some(wgpu::RasterizationStateDescriptor {
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: wgpu::CullMode::Back,
-                clamp_depth: false,
-                depth_bias: 0,
-                depth_bias_slope_scale: 0.0,
-                depth_bias_clamp: 0.0,
-            }),
-            primitive_topology: wgpu::PrimitiveTopology::TriangleList,
-            color_states: &[wgpu::ColorStateDescriptor {
-                format: sc_desc.format,
-                alpha_blend: wgpu::BlendDescriptor::REPLACE,
-                color_blend: wgpu::BlendDescriptor::REPLACE,
-                write_mask: wgpu::ColorWrite::ALL,
-            }],
-            depth_stencil_state: None,
-            vertex_state: wgpu::VertexStateDescriptor {
-                index_format: wgpu::IndexFormat::Uint16,
-                vertex_buffers: &[Vertex::BUFFER_DESCRIPTOR],
-            },
-            sample_count: 1,
-            sample_mask: !0,
-            alpha_to_coverage_enabled: true,
-        });
+        let render_pipelines = RenderPipelines {
+            layout: render_pipeline_layout,
+            vs_module: lock_device.create_shader_module(wgpu::include_spirv!("../shader.vert.spv")),
+            vs_color_module: lock_device
+                .create_shader_module(wgpu::include_spirv!("../shader_color.vert.spv")),
+            fs_module: lock_device.create_shader_module(wgpu::include_spirv!("../shader.frag.spv")),
+            vs_lit_module: lock_device
+                .create_shader_module(wgpu::include_spirv!("../shader_lit.vert.spv")),
+            fs_lit_module: lock_device
+                .create_shader_module(wgpu::include_spirv!("../shader_lit.frag.spv")),
+            pipelines: HashMap::new(),
+        };
+        let depth_settings = DepthSettings::default();
+        let depth_buffer =
+            DepthBuffer::new(&lock_device, sc_desc.width, sc_desc.height, &depth_settings);
+        let skybox_pass = SkyboxPass::new(
+            &lock_device,
+            &ressource_manager.texture_manager.cubemap_bind_group_layout,
+        );
 
         drop(lock_device);
         drop(lock_queue);
 
-        Ok(Self {
-            surface,
+        let mut scene = Scene::new();
+        scene
+            .camera
+            .set_viewport_size(sc_desc.width, sc_desc.height);
+
+        Self {
+            target,
             device,
             queue,
             sc_desc,
-            swap_chain,
-            render_pipeline,
-
-            mesh_id,
-            texture_id,
+            depth_settings,
+            depth_buffer,
+            target_depth_buffers: HashMap::new(),
+            render_pipelines,
 
             ressource_manager,
-        })
+            scene,
+            model_buffer,
+            instance_buffer,
+            camera_buffer,
+            skybox_pass,
+            capture_requested: false,
+            captured_frame: None,
+        }
+    }
+
+    /// Changes the depth and stencil configuration, recreating the depth buffer. Render
+    /// pipelines are recreated the next time they are used.
+    pub fn set_depth_settings(&mut self, depth_settings: DepthSettings) {
+        let device = self.device.lock().unwrap();
+
+        self.depth_buffer = DepthBuffer::new(
+            &device,
+            self.sc_desc.width,
+            self.sc_desc.height,
+            &depth_settings,
+        );
+        self.render_pipelines.pipelines.clear();
+        self.skybox_pass.clear_pipelines();
+        self.target_depth_buffers.clear();
+        self.depth_settings = depth_settings;
+    }
+
+    pub fn depth_settings(&self) -> &DepthSettings {
+        &self.depth_settings
     }
 
+    /// A minimized window has a null size, the swap chain and depth buffer of the last
+    /// size are kept until it is restored.
     pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size.width == 0 || new_size.height == 0 {
+            return;
+        }
         let device = self.device.lock().unwrap();
 
         self.sc_desc.width = new_size.width;
         self.sc_desc.height = new_size.height;
-        self.swap_chain = device.create_swap_chain(&self.surface, &self.sc_desc);
+        match &mut self.target {
+            RenderTarget::Window {
+                surface,
+                swap_chain,
+            } => *swap_chain = device.create_swap_chain(surface, &self.sc_desc),
+            RenderTarget::Headless { .. } => {
+                self.target = Self::create_headless_target(&device, &self.sc_desc)
+            }
+        }
+        self.depth_buffer = DepthBuffer::new(
+            &device,
+            new_size.width,
+            new_size.height,
+            &self.depth_settings,
+        );
+        self.scene
+            .camera
+            .set_viewport_size(new_size.width, new_size.height);
+    }
+
+    /// Renders the submitted draw items, the scene is emptied even if rendering fails.
+    ///
+    /// When the swap chain is lost or outdated it is recreated and the frame is skipped.
+    /// Items whose mesh or texture was dropped after being submitted fail the frame with
+    /// [`Error::StaleHandle`] before anything is drawn.
+    pub fn render(&mut self) -> Result<()> {
+        let result = self.render_scene().and_then(|()| self.capture_frame());
+        self.scene.clear();
+        result
+    }
+
+    /// The next frame rendered will be copied back to the cpu, to be retrieved with
+    /// [`Renderer::take_captured_frame`]. Unlike [`Renderer::read_frame`] this also works
+    /// when rendering into a window: swap chain frames can't be copied, so the scene is
+    /// drawn a second time into an offscreen texture, which costs a frame of gpu time.
+    pub fn capture_next_frame(&mut self) {
+        self.capture_requested = true;
+    }
+
+    pub fn take_captured_frame(&mut self) -> Option<image::RgbaImage> {
+        self.captured_frame.take()
+    }
+
+    fn capture_frame(&mut self) -> Result<()> {
+        if !self.capture_requested {
+            return Ok(());
+        }
+        self.capture_requested = false;
+
+        let size = (self.sc_desc.width, self.sc_desc.height);
+        let device = self.device.lock().unwrap();
+        let queue = self.queue.lock().unwrap();
+        let pixels = match &self.target {
+            RenderTarget::Headless { texture, .. } => {
+                readback::read_texture(&device, &queue, texture, 0, size, 4)?
+            }
+            // Swap chain frames can't be copied, the scene is drawn again offscreen.
+            RenderTarget::Window { .. } => {
+                let sc_desc = wgpu::SwapChainDescriptor {
+                    usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+                    ..self.sc_desc.clone()
+                };
+                let (texture, view) = match Self::create_headless_target(&device, &sc_desc) {
+                    RenderTarget::Headless { texture, view } => (texture, view),
+                    RenderTarget::Window { .. } => unreachable!(),
+                };
+
+                SceneDraw {
+                    device: &device,
+                    queue: &queue,
+                    ressource_manager: &self.ressource_manager,
+                    render_pipelines: &mut self.render_pipelines,
+                    depth_settings: &self.depth_settings,
+                    model_buffer: &mut self.model_buffer,
+                    instance_buffer: &mut self.instance_buffer,
+                    camera_buffer: &self.camera_buffer,
+                    skybox_pass: &mut self.skybox_pass,
+                }
+                .draw(
+                    &self.scene,
+                    &view,
+                    self.sc_desc.format,
+                    &self.depth_buffer.view,
+                )?;
+                readback::read_texture(&device, &queue, &texture, 0, size, 4)?
+            }
+        };
+
+        self.captured_frame = image::RgbaImage::from_raw(size.0, size.1, pixels);
+        Ok(())
     }
 
-    pub fn render(&mut self) {
+    fn render_scene(&mut self) -> Result<()> {
+        self.ressource_manager.check_scene(&self.scene)?;
+        // The camera may have been replaced since the last resize.
+        self.scene
+            .camera
+            .set_viewport_size(self.sc_desc.width, self.sc_desc.height);
+
+        // Offscreen passes are rendered first so the main scene can sample their result.
+        let texture_passes = std::mem::take(&mut self.scene.texture_passes);
+        self.render_texture_passes(&texture_passes)?;
+
         let device = self.device.lock().unwrap();
 
-        let output_texture = self.swap_chain.get_current_frame().unwrap().output;
-        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("render_pass command encoder"),
-        });
+        let frame;
+        let output_view = match &mut self.target {
+            RenderTarget::Window {
+                surface,
+                swap_chain,
+            } => {
+                frame = match swap_chain.get_current_frame() {
+                    Ok(frame) => frame,
+                    Err(err) => {
+                        if let wgpu::SwapChainError::Lost | wgpu::SwapChainError::Outdated = err {
+                            *swap_chain = device.create_swap_chain(surface, &self.sc_desc);
+                        }
+                        return Err(Error::SwapChainLost(err));
+                    }
+                };
+                &frame.output.view
+            }
+            RenderTarget::Headless { view, .. } => view,
+        };
 
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
-                    attachment: &output_texture.view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                        store: true,
-                    },
-                }],
-                depth_stencil_attachment: None,
-            });
+        let queue = self.queue.lock().unwrap();
+        SceneDraw {
+            device: &device,
+            queue: &queue,
+            ressource_manager: &self.ressource_manager,
+            render_pipelines: &mut self.render_pipelines,
+            depth_settings: &self.depth_settings,
+            model_buffer: &mut self.model_buffer,
+            instance_buffer: &mut self.instance_buffer,
+            camera_buffer: &self.camera_buffer,
+            skybox_pass: &mut self.skybox_pass,
+        }
+        .draw(
+            &self.scene,
+            output_view,
+            self.sc_desc.format,
+            &self.depth_buffer.view,
+        )
+    }
 
-            render_pass.set_pipeline(&self.render_pipeline);
-            self.ressource_manager.texture_manager.bind_texture(
-                &mut render_pass,
-                self.texture_id,
-                0,
-            );
-            self.ressource_manager
-                .mesh_manager
-                .draw_mesh(&mut render_pass, self.mesh_id);
+    /// Renders a scene into a texture view created with
+    /// [`TextureManager::create_render_target`](crate::ressource_manager::texture::TextureManager::create_render_target),
+    /// it can then be sampled like any other view. The offscreen passes of the scene are
+    /// rendered first. The aspect ratio of the camera of the scene is left to the caller.
+    ///
+    /// A texture can't be sampled while it is rendered to, and targets with more than
+    /// one mip level are rejected.
+    pub fn render_to_texture(&mut self, target: TextureViewId, scene: &Scene) -> Result<()> {
+        self.ressource_manager.check_scene(scene)?;
+        self.draw_to_texture(target, None, scene)
+    }
+
+    /// Same as [`Renderer::render_to_texture`] but the depth is written to a view created
+    /// with [`TextureManager::create_depth_target`](crate::ressource_manager::texture::TextureManager::create_depth_target),
+    /// of the size of `target`. Depth targets can't be used with stencil settings, the
+    /// depth buffer then has another format.
+    pub fn render_to_texture_with_depth(
+        &mut self,
+        target: TextureViewId,
+        depth_target: TextureViewId,
+        scene: &Scene,
+    ) -> Result<()> {
+        self.ressource_manager.check_scene(scene)?;
+        self.draw_to_texture(target, Some(depth_target), scene)
+    }
+
+    fn render_texture_passes(&mut self, texture_passes: &[TexturePass]) -> Result<()> {
+        for pass in texture_passes {
+            self.draw_to_texture(pass.target, pass.depth_target, &pass.scene)?;
         }
+        Ok(())
+    }
+
+    fn draw_to_texture(
+        &mut self,
+        target: TextureViewId,
+        depth_target: Option<TextureViewId>,
+        scene: &Scene,
+    ) -> Result<()> {
+        // The scene may sample the result of its own offscreen passes.
+        self.render_texture_passes(&scene.texture_passes)?;
+
+        let texture_manager = &self.ressource_manager.texture_manager;
+        let format = texture_manager.texture_view_format(target)?;
+        if format.is_depth() {
+            return Err(Error::RenderTargetFormat(format));
+        }
+        // Render passes can only write a single mip level.
+        let mip_level_count = texture_manager.texture_view_mip_level_count(target)?;
+        if mip_level_count > 1 {
+            return Err(Error::RenderTargetMipLevels(mip_level_count));
+        }
+        let size = texture_manager.texture_view_size(target)?;
+        if let Some(depth_target) = depth_target {
+            let depth_format = texture_manager.texture_view_format(depth_target)?;
+            if depth_format.wgpu_format() != self.depth_settings.format() {
+                return Err(Error::RenderTargetFormat(depth_format));
+            }
+            let depth_size = texture_manager.texture_view_size(depth_target)?;
+            if depth_size != size {
+                return Err(Error::RenderTargetSize {
+                    expected: size,
+                    found: depth_size,
+                });
+            }
+        }
+
+        let device = self.device.lock().unwrap();
+        let depth_view = match depth_target {
+            Some(depth_target) => texture_manager.view(depth_target)?,
+            None => {
+                let depth_settings = &self.depth_settings;
+                &self
+                    .target_depth_buffers
+                    .entry(size)
+                    .or_insert_with(|| DepthBuffer::new(&device, size.0, size.1, depth_settings))
+                    .view
+            }
+        };
 
         let queue = self.queue.lock().unwrap();
-        queue.submit(Some(encoder.finish()));
+        SceneDraw {
+            device: &device,
+            queue: &queue,
+            ressource_manager: &self.ressource_manager,
+            render_pipelines: &mut self.render_pipelines,
+            depth_settings: &self.depth_settings,
+            model_buffer: &mut self.model_buffer,
+            instance_buffer: &mut self.instance_buffer,
+            camera_buffer: &self.camera_buffer,
+            skybox_pass: &mut self.skybox_pass,
+        }
+        .draw(
+            scene,
+            texture_manager.view(target)?,
+            format.wgpu_format(),
+            depth_view,
+        )
     }
 
     pub fn update(&mut self) {}
+
+    /// Draw items submitted here are rendered by the next call to [`Renderer::render`].
+    pub fn scene_mut(&mut self) -> &mut Scene {
+        &mut self.scene
+    }
+
+    pub fn ressource_manager(&self) -> &RessourceManager {
+        &self.ressource_manager
+    }
+
+    pub fn ressource_manager_mut(&mut self) -> &mut RessourceManager {
+        &mut self.ressource_manager
+    }
+
+    /// Copies the last rendered frame back to the cpu.
+    /// Returns `None` when rendering into a window, the swap chain frames can't be read.
+    pub fn read_frame(&self) -> Result<Option<image::RgbaImage>> {
+        let texture = match &self.target {
+            RenderTarget::Window { .. } => return Ok(None),
+            RenderTarget::Headless { texture, .. } => texture,
+        };
+
+        let width = self.sc_desc.width;
+        let height = self.sc_desc.height;
+        let device = self.device.lock().unwrap();
+        let queue = self.queue.lock().unwrap();
+        let pixels = readback::read_texture(&device, &queue, texture, 0, (width, height), 4)?;
+
+        Ok(image::RgbaImage::from_raw(width, height, pixels))
+    }
 }
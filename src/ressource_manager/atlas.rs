@@ -0,0 +1,153 @@
+//! Packing of many images into a single texture, so they can be drawn without
+//! switching bind groups.
+
+use super::texture::{TextureManager, TextureViewId};
+use crate::error::{Error, Result};
+use crate::geometry::UvRect;
+
+use image::RgbaImage;
+
+/// Position of a packed image in the atlas, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtlasRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Result of [`pack`], regions are in the order of the given sizes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AtlasLayout {
+    pub width: u32,
+    pub height: u32,
+    pub regions: Vec<AtlasRegion>,
+}
+
+impl AtlasLayout {
+    pub fn uv_rect(&self, region: AtlasRegion) -> UvRect {
+        UvRect {
+            min: [
+                region.x as f32 / self.width as f32,
+                region.y as f32 / self.height as f32,
+            ],
+            max: [
+                (region.x + region.width) as f32 / self.width as f32,
+                (region.y + region.height) as f32 / self.height as f32,
+            ],
+        }
+    }
+}
+
+/// Packs rectangles in rows, tallest first. `padding` pixels are kept around each
+/// rectangle so filtering and mipmaps don't bleed between neighbours.
+///
+/// The atlas is as wide as the square root of the total area rounded up to a power
+/// of two, its height is only what the rows need.
+pub fn pack(sizes: &[(u32, u32)], padding: u32) -> AtlasLayout {
+    let padded = |(width, height): (u32, u32)| (width + 2 * padding, height + 2 * padding);
+
+    let area: u64 = sizes
+        .iter()
+        .map(|&size| {
+            let (width, height) = padded(size);
+            width as u64 * height as u64
+        })
+        .sum();
+    let widest = sizes.iter().map(|&size| padded(size).0).max().unwrap_or(1);
+    let width = ((area as f64).sqrt().ceil() as u32)
+        .max(widest)
+        .next_power_of_two();
+
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(sizes[i].1));
+
+    let mut regions = vec![
+        AtlasRegion {
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+        };
+        sizes.len()
+    ];
+    let (mut x, mut y, mut row_height) = (0, 0, 0);
+    for i in order {
+        let (padded_width, padded_height) = padded(sizes[i]);
+        if x + padded_width > width {
+            x = 0;
+            y += row_height;
+            row_height = 0;
+        }
+
+        regions[i] = AtlasRegion {
+            x: x + padding,
+            y: y + padding,
+            width: sizes[i].0,
+            height: sizes[i].1,
+        };
+        x += padded_width;
+        row_height = row_height.max(padded_height);
+    }
+
+    AtlasLayout {
+        width,
+        height: (y + row_height).max(1),
+        regions,
+    }
+}
+
+/// Copies the images at their place in the atlas, the padding is filled by
+/// repeating the border pixels of each image.
+pub fn build_atlas_image(images: &[RgbaImage], layout: &AtlasLayout, padding: u32) -> RgbaImage {
+    let mut atlas = RgbaImage::new(layout.width, layout.height);
+    for (image, region) in images.iter().zip(layout.regions.iter()) {
+        let padding = padding as i64;
+        for y in -padding..region.height as i64 + padding {
+            for x in -padding..region.width as i64 + padding {
+                let source_x = x.clamp(0, region.width as i64 - 1) as u32;
+                let source_y = y.clamp(0, region.height as i64 - 1) as u32;
+                atlas.put_pixel(
+                    (region.x as i64 + x) as u32,
+                    (region.y as i64 + y) as u32,
+                    *image.get_pixel(source_x, source_y),
+                );
+            }
+        }
+    }
+    atlas
+}
+
+/// Many images packed in one texture view.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Atlas {
+    pub view: TextureViewId,
+    /// Area of each image, in the order they were given.
+    pub uv_rects: Vec<UvRect>,
+}
+
+impl TextureManager {
+    /// Packs the images in a single sRGB texture view.
+    pub fn create_atlas(&mut self, images: &[RgbaImage], padding: u32) -> Result<Atlas> {
+        if images.is_empty()
+            || images
+                .iter()
+                .any(|image| image.width() == 0 || image.height() == 0)
+        {
+            return Err(Error::EmptyAtlas);
+        }
+
+        let sizes: Vec<(u32, u32)> = images.iter().map(|image| image.dimensions()).collect();
+        let layout = pack(&sizes, padding);
+        let atlas = build_atlas_image(images, &layout, padding);
+
+        Ok(Atlas {
+            view: self.create_texture_view_from_image(&atlas),
+            uv_rects: layout
+                .regions
+                .iter()
+                .map(|region| layout.uv_rect(*region))
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,96 @@
+//! Cubemap faces and their creation from equirectangular panoramas.
+
+use super::format::{HdrImage, TextureImage};
+
+use cgmath::{InnerSpace, Vector3};
+
+use std::f32::consts::PI;
+
+/// Faces in the order of the layers of a cubemap texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubeFace {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl CubeFace {
+    pub const ALL: [CubeFace; 6] = [
+        CubeFace::PositiveX,
+        CubeFace::NegativeX,
+        CubeFace::PositiveY,
+        CubeFace::NegativeY,
+        CubeFace::PositiveZ,
+        CubeFace::NegativeZ,
+    ];
+
+    /// Direction sampled at a point of the face, `u` and `v` go from -1 to 1,
+    /// left to right and top to bottom.
+    pub fn direction(self, u: f32, v: f32) -> Vector3<f32> {
+        let direction = match self {
+            CubeFace::PositiveX => Vector3::new(1.0, -v, -u),
+            CubeFace::NegativeX => Vector3::new(-1.0, -v, u),
+            CubeFace::PositiveY => Vector3::new(u, 1.0, v),
+            CubeFace::NegativeY => Vector3::new(u, -1.0, -v),
+            CubeFace::PositiveZ => Vector3::new(u, -v, 1.0),
+            CubeFace::NegativeZ => Vector3::new(-u, -v, -1.0),
+        };
+        direction.normalize()
+    }
+}
+
+/// Coordinates in an equirectangular panorama of a direction, the center of the image
+/// looks toward -z and its top toward +y.
+pub fn equirectangular_uv(direction: Vector3<f32>) -> [f32; 2] {
+    let direction = direction.normalize();
+    let u = 0.5 + direction.x.atan2(-direction.z) / (2.0 * PI);
+    let v = direction.y.clamp(-1.0, 1.0).acos() / PI;
+    [u, v]
+}
+
+/// Projects a panorama on the six faces of a cube of `size` pixels wide faces.
+pub fn equirectangular_to_cubemap(panorama: &TextureImage, size: u32) -> [TextureImage; 6] {
+    let panorama = match panorama {
+        TextureImage::Hdr(image) => image.clone(),
+        TextureImage::Ldr(image) => HdrImage::from_fn(image.width(), image.height(), |x, y| {
+            let [r, g, b, a] = image.get_pixel(x, y).0;
+            image::Rgba([r, g, b, a].map(|value| value as f32 / 255.0))
+        }),
+    };
+
+    CubeFace::ALL.map(|face| {
+        TextureImage::Hdr(HdrImage::from_fn(size, size, |x, y| {
+            let u = 2.0 * (x as f32 + 0.5) / size as f32 - 1.0;
+            let v = 2.0 * (y as f32 + 0.5) / size as f32 - 1.0;
+            image::Rgba(sample_bilinear(
+                &panorama,
+                equirectangular_uv(face.direction(u, v)),
+            ))
+        }))
+    })
+}
+
+/// Wraps horizontally and clamps vertically.
+fn sample_bilinear(image: &HdrImage, [u, v]: [f32; 2]) -> [f32; 4] {
+    let (width, height) = image.dimensions();
+    let x = u * width as f32 - 0.5;
+    let y = (v * height as f32 - 0.5).clamp(0.0, height as f32 - 1.0);
+
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let (tx, ty) = (x - x0, y - y0);
+    let wrap = |x: f32| (x as i64).rem_euclid(width as i64) as u32;
+    let clamp = |y: f32| (y as u32).min(height - 1);
+
+    let texel = |x: f32, y: f32| image.get_pixel(wrap(x), clamp(y)).0;
+    let lerp = |a: [f32; 4], b: [f32; 4], t: f32| [0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t);
+
+    lerp(
+        lerp(texel(x0, y0), texel(x0 + 1.0, y0), tx),
+        lerp(texel(x0, y0 + 1.0), texel(x0 + 1.0, y0 + 1.0), tx),
+        ty,
+    )
+}
@@ -0,0 +1,282 @@
+//! OpenEXR decoding, limited to what textures need: single part scanline images,
+//! uncompressed or zip compressed, with half, float or uint channels.
+
+use super::format::{f16_to_f32, HdrImage};
+use crate::error::{Error, Result};
+
+const MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+// Flags of the version field.
+const TILED: u32 = 0x200;
+const DEEP: u32 = 0x800;
+const MULTIPART: u32 = 0x1000;
+
+const NO_COMPRESSION: u8 = 0;
+const ZIPS_COMPRESSION: u8 = 2;
+const ZIP_COMPRESSION: u8 = 3;
+
+/// Largest width or height accepted, bigger images couldn't be uploaded anyway.
+const MAX_SIZE: i64 = 16384;
+
+pub(crate) fn is_exr(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PixelType {
+    Uint,
+    Half,
+    Float,
+}
+
+impl PixelType {
+    fn size(self) -> usize {
+        match self {
+            PixelType::Half => 2,
+            PixelType::Uint | PixelType::Float => 4,
+        }
+    }
+}
+
+struct Channel {
+    name: String,
+    pixel_type: PixelType,
+}
+
+/// Reads the file front to back, failing instead of panicking on truncated data.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.bytes.len() {
+            return Err(Error::Exr("unexpected end of file"));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn i32(&mut self) -> Result<i32> {
+        let bytes = self.take(4)?;
+        Ok(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(self.i32()? as u32)
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// Null terminated string, empty at the end of the header and of the channel list.
+    fn string(&mut self) -> Result<String> {
+        let len = self
+            .bytes
+            .iter()
+            .position(|byte| *byte == 0)
+            .ok_or(Error::Exr("unterminated string"))?;
+        let string = String::from_utf8_lossy(self.take(len)?).into_owned();
+        self.take(1)?;
+        Ok(string)
+    }
+}
+
+struct Header {
+    channels: Vec<Channel>,
+    compression: u8,
+    /// Minimum and maximum pixel coordinates, inclusive.
+    data_window: [i32; 4],
+}
+
+fn read_header(reader: &mut Reader) -> Result<Header> {
+    let mut channels = None;
+    let mut compression = None;
+    let mut data_window = None;
+
+    loop {
+        let name = reader.string()?;
+        if name.is_empty() {
+            break;
+        }
+        let _attribute_type = reader.string()?;
+        let size = reader.i32()?;
+        if size < 0 {
+            return Err(Error::Exr("negative attribute size"));
+        }
+        let mut value = Reader {
+            bytes: reader.take(size as usize)?,
+        };
+
+        match name.as_str() {
+            "channels" => {
+                let mut list = Vec::new();
+                loop {
+                    let name = value.string()?;
+                    if name.is_empty() {
+                        break;
+                    }
+                    let pixel_type = match value.i32()? {
+                        0 => PixelType::Uint,
+                        1 => PixelType::Half,
+                        2 => PixelType::Float,
+                        _ => return Err(Error::Exr("unknown channel type")),
+                    };
+                    // pLinear and reserved bytes.
+                    value.take(4)?;
+                    if value.i32()? != 1 || value.i32()? != 1 {
+                        return Err(Error::Exr("subsampled channels are not supported"));
+                    }
+                    list.push(Channel { name, pixel_type });
+                }
+                channels = Some(list);
+            }
+            "compression" => compression = Some(value.u8()?),
+            "dataWindow" => {
+                data_window = Some([value.i32()?, value.i32()?, value.i32()?, value.i32()?])
+            }
+            _ => {}
+        }
+    }
+
+    Ok(Header {
+        channels: channels.ok_or(Error::Exr("missing channels"))?,
+        compression: compression.ok_or(Error::Exr("missing compression"))?,
+        data_window: data_window.ok_or(Error::Exr("missing data window"))?,
+    })
+}
+
+/// Undoes the byte reordering and delta encoding applied before zip compression.
+fn unpredict(data: &mut [u8]) -> Vec<u8> {
+    for i in 1..data.len() {
+        data[i] = data[i - 1].wrapping_add(data[i]).wrapping_sub(128);
+    }
+
+    let (first, second) = data.split_at(data.len().div_ceil(2));
+    let mut bytes = Vec::with_capacity(data.len());
+    for (i, byte) in first.iter().enumerate() {
+        bytes.push(*byte);
+        bytes.extend(second.get(i));
+    }
+    bytes
+}
+
+/// Decodes an EXR file into floats, `R`, `G`, `B` and `A` channels are used when present,
+/// a single `Y` channel is used as a gray level. Missing colors are 0, missing alpha is 1.
+pub(crate) fn decode(bytes: &[u8]) -> Result<HdrImage> {
+    let mut reader = Reader { bytes };
+    if reader.take(4)? != MAGIC {
+        return Err(Error::Exr("not an EXR file"));
+    }
+    let version = reader.u32()?;
+    if version & (TILED | DEEP | MULTIPART) != 0 {
+        return Err(Error::Exr("only single part scanline files are supported"));
+    }
+
+    let header = read_header(&mut reader)?;
+    let lines_per_chunk = match header.compression {
+        NO_COMPRESSION | ZIPS_COMPRESSION => 1,
+        ZIP_COMPRESSION => 16,
+        _ => return Err(Error::Exr("unsupported compression")),
+    };
+    let [x_min, y_min, x_max, y_max] = header.data_window;
+    // Computed on 64 bits since the window can span the whole i32 range.
+    let width = x_max as i64 - x_min as i64 + 1;
+    let height = y_max as i64 - y_min as i64 + 1;
+    if width <= 0 || height <= 0 {
+        return Err(Error::Exr("empty data window"));
+    }
+    if width > MAX_SIZE || height > MAX_SIZE {
+        return Err(Error::Exr("data window too large"));
+    }
+    let (width, height) = (width as usize, height as usize);
+
+    let nb_chunks = height.div_ceil(lines_per_chunk);
+    let offsets = (0..nb_chunks)
+        .map(|_| reader.u64())
+        .collect::<Result<Vec<_>>>()?;
+
+    let target = |name: &str| match name {
+        "R" => Some(0),
+        "G" => Some(1),
+        "B" => Some(2),
+        "A" => Some(3),
+        _ => None,
+    };
+    let gray = header.channels.len() == 1 && header.channels[0].name == "Y";
+    let line_size: usize = header
+        .channels
+        .iter()
+        .map(|channel| channel.pixel_type.size() * width)
+        .sum();
+
+    let mut pixels = vec![0.0; width * height * 4];
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel[3] = 1.0;
+    }
+
+    for offset in offsets {
+        let mut chunk = Reader {
+            bytes: bytes
+                .get(offset as usize..)
+                .ok_or(Error::Exr("chunk offset out of the file"))?,
+        };
+        let y = chunk.i32()?;
+        let size = chunk.i32()?;
+        if y < y_min || y > y_max || size < 0 {
+            return Err(Error::Exr("invalid chunk"));
+        }
+        let first_line = (y - y_min) as usize;
+        let nb_lines = lines_per_chunk.min(height - first_line);
+        let expected = line_size * nb_lines;
+
+        let data = chunk.take(size as usize)?;
+        // Chunks that don't shrink when compressed are stored as is.
+        let data = if header.compression == NO_COMPRESSION || data.len() >= expected {
+            data.to_vec()
+        } else {
+            let mut data = miniz_oxide::inflate::decompress_to_vec_zlib(data)
+                .map_err(|_| Error::Exr("invalid zip data"))?;
+            unpredict(&mut data)
+        };
+        if data.len() != expected {
+            return Err(Error::Exr("unexpected chunk size"));
+        }
+
+        // Each line holds all the values of its first channel, then of the next ones.
+        let mut values = Reader { bytes: &data };
+        for line in first_line..first_line + nb_lines {
+            for channel in &header.channels {
+                let target = if gray { Some(0) } else { target(&channel.name) };
+                for x in 0..width {
+                    let bytes = values.take(channel.pixel_type.size())?;
+                    let value = match channel.pixel_type {
+                        PixelType::Half => f16_to_f32(u16::from_le_bytes([bytes[0], bytes[1]])),
+                        PixelType::Float => {
+                            f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+                        }
+                        PixelType::Uint => {
+                            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32
+                        }
+                    };
+
+                    let pixel = &mut pixels[(line * width + x) * 4..][..4];
+                    match target {
+                        Some(_) if gray => pixel[..3].copy_from_slice(&[value; 3]),
+                        Some(target) => pixel[target] = value,
+                        None => {}
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(HdrImage::from_raw(width as u32, height as u32, pixels).unwrap())
+}
@@ -0,0 +1,258 @@
+//! Texture formats and the conversion of decoded images to their texel layout.
+
+use super::exr;
+use crate::error::Result;
+
+use image::{ImageBuffer, Rgba, RgbaImage};
+
+/// Floating point RGBA image, used for high dynamic range data.
+pub type HdrImage = ImageBuffer<Rgba<f32>, Vec<f32>>;
+
+/// Format of the texels of a texture view.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextureFormat {
+    /// Color data, decoded from sRGB when sampled.
+    #[default]
+    Rgba8Srgb,
+    /// Data that isn't a color, like normal maps.
+    Rgba8Linear,
+    /// Single channel, like roughness or height maps. Filled with the luminance of the image.
+    R8,
+    /// Two channels, filled with the red and green channels of the image.
+    Rg8,
+    Rgba16Float,
+    Rgba32Float,
+    /// Depth of a render target, see
+    /// [`TextureManager::create_depth_target`](super::texture::TextureManager::create_depth_target).
+    /// It is sampled in the red channel.
+    Depth32Float,
+}
+
+impl TextureFormat {
+    pub fn wgpu_format(self) -> wgpu::TextureFormat {
+        match self {
+            TextureFormat::Rgba8Srgb => wgpu::TextureFormat::Rgba8UnormSrgb,
+            TextureFormat::Rgba8Linear => wgpu::TextureFormat::Rgba8Unorm,
+            TextureFormat::R8 => wgpu::TextureFormat::R8Unorm,
+            TextureFormat::Rg8 => wgpu::TextureFormat::Rg8Unorm,
+            TextureFormat::Rgba16Float => wgpu::TextureFormat::Rgba16Float,
+            TextureFormat::Rgba32Float => wgpu::TextureFormat::Rgba32Float,
+            TextureFormat::Depth32Float => wgpu::TextureFormat::Depth32Float,
+        }
+    }
+
+    pub fn bytes_per_pixel(self) -> u32 {
+        match self {
+            TextureFormat::R8 => 1,
+            TextureFormat::Rg8 => 2,
+            TextureFormat::Rgba8Srgb | TextureFormat::Rgba8Linear => 4,
+            TextureFormat::Depth32Float => 4,
+            TextureFormat::Rgba16Float => 8,
+            TextureFormat::Rgba32Float => 16,
+        }
+    }
+
+    /// 32 bits float textures can't be filtered, no mip chain is generated for them.
+    pub fn is_filterable(self) -> bool {
+        !matches!(
+            self,
+            TextureFormat::Rgba32Float | TextureFormat::Depth32Float
+        )
+    }
+
+    pub fn is_depth(self) -> bool {
+        self == TextureFormat::Depth32Float
+    }
+}
+
+/// A decoded image, either 8 bits per channel or floating point.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextureImage {
+    Ldr(RgbaImage),
+    Hdr(HdrImage),
+}
+
+impl TextureImage {
+    /// Decodes any format supported by `image`, Radiance `.hdr` and OpenEXR files are
+    /// kept as floats.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        if exr::is_exr(bytes) {
+            return Ok(TextureImage::Hdr(exr::decode(bytes)?));
+        }
+        match image::guess_format(bytes)? {
+            image::ImageFormat::Hdr => {
+                let decoder = image::hdr::HdrDecoder::new(bytes)?;
+                let metadata = decoder.metadata();
+                let pixels = decoder
+                    .read_image_hdr()?
+                    .into_iter()
+                    .flat_map(|pixel| {
+                        let [r, g, b] = pixel.0;
+                        vec![r, g, b, 1.0]
+                    })
+                    .collect();
+                Ok(TextureImage::Hdr(
+                    HdrImage::from_raw(metadata.width, metadata.height, pixels).unwrap(),
+                ))
+            }
+            _ => Ok(TextureImage::Ldr(
+                image::load_from_memory(bytes)?.to_rgba8(),
+            )),
+        }
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        match self {
+            TextureImage::Ldr(image) => image.dimensions(),
+            TextureImage::Hdr(image) => image.dimensions(),
+        }
+    }
+
+    /// Texels in the layout of the format. 8 bits images are not linearized when
+    /// converted to floats and floats are clamped to `0.0..=1.0` when converted to 8 bits.
+    pub fn to_bytes(&self, format: TextureFormat) -> Vec<u8> {
+        match format {
+            TextureFormat::Rgba8Srgb | TextureFormat::Rgba8Linear => {
+                self.unorm_channels(&[0, 1, 2, 3])
+            }
+            TextureFormat::R8 => match self {
+                TextureImage::Ldr(image) => image::DynamicImage::ImageRgba8(image.clone())
+                    .to_luma8()
+                    .into_raw(),
+                TextureImage::Hdr(_) => self.unorm_channels(&[0]),
+            },
+            TextureFormat::Rg8 => self.unorm_channels(&[0, 1]),
+            TextureFormat::Rgba16Float => self
+                .float_channels()
+                .flat_map(|value| f32_to_f16(value).to_le_bytes().to_vec())
+                .collect(),
+            TextureFormat::Rgba32Float => self
+                .float_channels()
+                .flat_map(|value| value.to_le_bytes().to_vec())
+                .collect(),
+            TextureFormat::Depth32Float => self
+                .float_channels()
+                .step_by(4)
+                .flat_map(|value| value.to_le_bytes().to_vec())
+                .collect(),
+        }
+    }
+
+    fn unorm_channels(&self, channels: &[usize]) -> Vec<u8> {
+        match self {
+            TextureImage::Ldr(image) => image
+                .pixels()
+                .flat_map(|pixel| channels.iter().map(move |&c| pixel.0[c]))
+                .collect(),
+            TextureImage::Hdr(image) => image
+                .pixels()
+                .flat_map(|pixel| {
+                    channels
+                        .iter()
+                        .map(move |&c| (pixel.0[c].clamp(0.0, 1.0) * 255.0).round() as u8)
+                })
+                .collect(),
+        }
+    }
+
+    fn float_channels(&self) -> Box<dyn Iterator<Item = f32> + '_> {
+        match self {
+            TextureImage::Ldr(image) => Box::new(image.iter().map(|&value| value as f32 / 255.0)),
+            TextureImage::Hdr(image) => Box::new(image.iter().copied()),
+        }
+    }
+}
+
+/// Converts texels in the layout of a format back to an 8 bits image, the inverse of
+/// [`TextureImage::to_bytes`]. Missing channels are filled like the shaders sample them:
+/// 0 for colors and 1 for alpha.
+pub fn texels_to_rgba(
+    format: TextureFormat,
+    (width, height): (u32, u32),
+    texels: &[u8],
+) -> RgbaImage {
+    let unorm = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let pixels = match format {
+        TextureFormat::Rgba8Srgb | TextureFormat::Rgba8Linear => texels.to_vec(),
+        TextureFormat::R8 => texels.iter().flat_map(|&r| vec![r, 0, 0, 255]).collect(),
+        TextureFormat::Rg8 => texels
+            .chunks_exact(2)
+            .flat_map(|rg| vec![rg[0], rg[1], 0, 255])
+            .collect(),
+        TextureFormat::Rgba16Float => texels
+            .chunks_exact(2)
+            .map(|half| unorm(f16_to_f32(u16::from_le_bytes([half[0], half[1]]))))
+            .collect(),
+        TextureFormat::Rgba32Float => texels
+            .chunks_exact(4)
+            .map(|float| unorm(f32::from_le_bytes([float[0], float[1], float[2], float[3]])))
+            .collect(),
+        TextureFormat::Depth32Float => texels
+            .chunks_exact(4)
+            .flat_map(|float| {
+                let depth = unorm(f32::from_le_bytes([float[0], float[1], float[2], float[3]]));
+                vec![depth, 0, 0, 255]
+            })
+            .collect(),
+    };
+    RgbaImage::from_raw(width, height, pixels).unwrap()
+}
+
+/// Converts to an IEEE 754 half precision float, rounding to nearest even.
+pub fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+
+    if exponent == 0xff {
+        // Infinity or NaN, NaNs keep a mantissa bit set.
+        let nan = if mantissa != 0 { 0x0200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    if exponent <= 0 {
+        // Subnormal or zero.
+        if exponent < -10 {
+            return sign;
+        }
+        let mantissa = mantissa | 0x0080_0000;
+        let shift = (14 - exponent) as u32;
+        let half = mantissa >> shift;
+        let rest = mantissa & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        let round = rest > halfway || (rest == halfway && half & 1 == 1);
+        return sign | (half + round as u32) as u16;
+    }
+
+    let half = ((exponent as u32) << 10) | (mantissa >> 13);
+    let rest = mantissa & 0x1fff;
+    let round = rest > 0x1000 || (rest == 0x1000 && half & 1 == 1);
+    // A carry out of the mantissa correctly bumps the exponent, up to infinity.
+    sign | (half + round as u32) as u16
+}
+
+/// Converts from an IEEE 754 half precision float, exactly.
+pub fn f16_to_f32(half: u16) -> f32 {
+    let sign = ((half & 0x8000) as u32) << 16;
+    let exponent = ((half >> 10) & 0x1f) as u32;
+    let mantissa = (half & 0x03ff) as u32;
+
+    let bits = match exponent {
+        0 if mantissa == 0 => sign,
+        // Subnormal, normalized for the larger exponent range of f32.
+        0 => {
+            let shift = mantissa.leading_zeros() - 21;
+            let mantissa = (mantissa << shift) & 0x03ff;
+            sign | ((127 - 15 + 1 - shift) << 23) | (mantissa << 13)
+        }
+        0x1f => sign | 0x7f80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 127 - 15) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(bits)
+}
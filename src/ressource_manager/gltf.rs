@@ -0,0 +1,428 @@
+//! glTF 2.0 (`.gltf` and `.glb`) scene import.
+
+use super::mesh::MeshId;
+use super::texture::{SamplerId, TextureId, TextureViewId};
+use super::RessourceManager;
+use crate::error::{Error, Result};
+use crate::geometry::{compute_normals, LitVertex};
+use crate::scene::{DrawItem, Scene};
+
+use ::gltf::image::Format;
+use ::gltf::mesh::Mode;
+use ::gltf::texture::{MagFilter, MinFilter, WrappingMode};
+use cgmath::Matrix4;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A glTF file uploaded to the gpu.
+///
+/// Nodes, meshes and materials keep the indices they have in the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfScene {
+    pub nodes: Vec<GltfNode>,
+    /// Root nodes of the default scene of the file, or of its first scene.
+    pub roots: Vec<usize>,
+    pub meshes: Vec<GltfMesh>,
+    pub materials: Vec<GltfMaterial>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfNode {
+    pub name: Option<String>,
+    /// Transform relative to the parent node.
+    pub transform: Matrix4<f32>,
+    pub children: Vec<usize>,
+    pub mesh: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfMesh {
+    pub name: Option<String>,
+    pub primitives: Vec<GltfPrimitive>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GltfPrimitive {
+    pub mesh: MeshId,
+    /// Index in [`GltfScene::materials`], `None` for the glTF default material.
+    pub material: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfMaterial {
+    pub name: Option<String>,
+    /// Already multiplied with the vertex colors of the primitives using the material.
+    pub base_color_factor: [f32; 4],
+    pub base_color_texture: Option<TextureId>,
+}
+
+impl GltfScene {
+    /// World transform of every node of the scene hierarchy, indexed like [`GltfScene::nodes`].
+    /// Nodes not reachable from the roots have no transform.
+    pub fn world_transforms(&self, root_transform: Matrix4<f32>) -> Vec<Option<Matrix4<f32>>> {
+        let mut transforms = vec![None; self.nodes.len()];
+        let mut stack: Vec<(usize, Matrix4<f32>)> = self
+            .roots
+            .iter()
+            .map(|root| (*root, root_transform))
+            .collect();
+
+        while let Some((node, parent_transform)) = stack.pop() {
+            let transform = parent_transform * self.nodes[node].transform;
+            transforms[node] = Some(transform);
+            stack.extend(
+                self.nodes[node]
+                    .children
+                    .iter()
+                    .map(|child| (*child, transform)),
+            );
+        }
+
+        transforms
+    }
+
+    /// Submits every primitive of the scene hierarchy.
+    /// `fallback_texture` is used by the primitives whose material has no base color texture.
+    pub fn submit(
+        &self,
+        scene: &mut Scene,
+        root_transform: Matrix4<f32>,
+        fallback_texture: TextureId,
+    ) {
+        for (node, transform) in self.nodes.iter().zip(self.world_transforms(root_transform)) {
+            let (mesh, transform) = match (node.mesh, transform) {
+                (Some(mesh), Some(transform)) => (&self.meshes[mesh], transform),
+                _ => continue,
+            };
+
+            for primitive in &mesh.primitives {
+                let texture = primitive
+                    .material
+                    .and_then(|material| self.materials[material].base_color_texture)
+                    .unwrap_or(fallback_texture);
+
+                scene.submit(DrawItem {
+                    mesh: primitive.mesh,
+                    texture,
+                    transform,
+                });
+            }
+        }
+    }
+}
+
+/// Converts the pixels decoded by the gltf crate to RGBA8.
+/// 16 bits channels are truncated to 8 bits.
+pub fn image_to_rgba(data: &::gltf::image::Data) -> image::RgbaImage {
+    let (channels, wide) = match data.format {
+        Format::R8 => (1, false),
+        Format::R8G8 => (2, false),
+        Format::R8G8B8 | Format::B8G8R8 => (3, false),
+        Format::R8G8B8A8 | Format::B8G8R8A8 => (4, false),
+        Format::R16 => (1, true),
+        Format::R16G16 => (2, true),
+        Format::R16G16B16 => (3, true),
+        Format::R16G16B16A16 => (4, true),
+    };
+    let bgr = matches!(data.format, Format::B8G8R8 | Format::B8G8R8A8);
+
+    let channel_size = if wide { 2 } else { 1 };
+    let mut pixels = Vec::with_capacity((data.width * data.height * 4) as usize);
+    for pixel in data.pixels.chunks_exact(channels * channel_size) {
+        let channel = |i: usize| {
+            if wide {
+                (u16::from_ne_bytes([pixel[2 * i], pixel[2 * i + 1]]) >> 8) as u8
+            } else {
+                pixel[i]
+            }
+        };
+
+        let rgba = match channels {
+            1 => [channel(0), channel(0), channel(0), 255],
+            2 => [channel(0), channel(0), channel(0), channel(1)],
+            3 => [channel(0), channel(1), channel(2), 255],
+            _ => [channel(0), channel(1), channel(2), channel(3)],
+        };
+        if bgr {
+            pixels.extend_from_slice(&[rgba[2], rgba[1], rgba[0], rgba[3]]);
+        } else {
+            pixels.extend_from_slice(&rgba);
+        }
+    }
+
+    image::RgbaImage::from_raw(data.width, data.height, pixels).unwrap()
+}
+
+/// Sampler descriptor matching a glTF sampler.
+pub fn sampler_descriptor(sampler: &::gltf::texture::Sampler) -> wgpu::SamplerDescriptor<'static> {
+    let address_mode = |mode| match mode {
+        WrappingMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+        WrappingMode::MirroredRepeat => wgpu::AddressMode::MirrorRepeat,
+        WrappingMode::Repeat => wgpu::AddressMode::Repeat,
+    };
+    let (min_filter, mipmap_filter) = match sampler.min_filter() {
+        Some(MinFilter::Nearest) | Some(MinFilter::NearestMipmapNearest) => {
+            (wgpu::FilterMode::Nearest, wgpu::FilterMode::Nearest)
+        }
+        Some(MinFilter::NearestMipmapLinear) => {
+            (wgpu::FilterMode::Nearest, wgpu::FilterMode::Linear)
+        }
+        Some(MinFilter::LinearMipmapNearest) => {
+            (wgpu::FilterMode::Linear, wgpu::FilterMode::Nearest)
+        }
+        Some(MinFilter::Linear) | Some(MinFilter::LinearMipmapLinear) | None => {
+            (wgpu::FilterMode::Linear, wgpu::FilterMode::Linear)
+        }
+    };
+    let mag_filter = match sampler.mag_filter() {
+        Some(MagFilter::Nearest) => wgpu::FilterMode::Nearest,
+        Some(MagFilter::Linear) | None => wgpu::FilterMode::Linear,
+    };
+
+    wgpu::SamplerDescriptor {
+        label: None,
+        address_mode_u: address_mode(sampler.wrap_s()),
+        address_mode_v: address_mode(sampler.wrap_t()),
+        address_mode_w: wgpu::AddressMode::Repeat,
+        mag_filter,
+        min_filter,
+        mipmap_filter,
+        ..Default::default()
+    }
+}
+
+/// Indices of a triangle list drawing the same triangles as a primitive, `None` for
+/// points and lines. `indices` are the indices of the primitive, or `0..count` when it
+/// has none.
+pub fn triangle_list(mode: Mode, indices: &[u32]) -> Option<Vec<u32>> {
+    let triangles = indices.len().saturating_sub(2);
+    match mode {
+        Mode::Triangles => Some(indices.to_vec()),
+        Mode::TriangleStrip => Some(
+            (0..triangles)
+                // Every other triangle is flipped to keep the winding order.
+                .flat_map(|i| match i % 2 {
+                    0 => [indices[i], indices[i + 1], indices[i + 2]],
+                    _ => [indices[i + 1], indices[i], indices[i + 2]],
+                })
+                .collect(),
+        ),
+        Mode::TriangleFan => Some(
+            (0..triangles)
+                .flat_map(|i| [indices[0], indices[i + 1], indices[i + 2]])
+                .collect(),
+        ),
+        Mode::Points | Mode::Lines | Mode::LineLoop | Mode::LineStrip => None,
+    }
+}
+
+/// Ressources created while loading a glTF file, released if the loading fails.
+#[derive(Default)]
+struct GltfRessources {
+    views: HashMap<usize, TextureViewId>,
+    samplers: HashMap<Option<usize>, SamplerId>,
+    textures: HashMap<usize, TextureId>,
+    meshes: Vec<MeshId>,
+}
+
+impl RessourceManager {
+    /// Loads a `.gltf` or `.glb` file with its buffers and textures, embedded or external.
+    /// Triangle strips and fans are converted to triangle lists, points and lines are
+    /// not supported.
+    pub fn load_gltf(&mut self, path: impl AsRef<Path>) -> Result<GltfScene> {
+        let (document, buffers, images) = ::gltf::import(path.as_ref()).map_err(Error::Gltf)?;
+
+        let mut ressources = GltfRessources::default();
+        let result = self
+            .load_gltf_materials(&document, &images, &mut ressources)
+            .and_then(|materials| {
+                let meshes = self.load_gltf_meshes(&document, &buffers, &mut ressources)?;
+                Ok((materials, meshes))
+            });
+
+        // The textures keep their views and samplers alive.
+        let mut released = Ok(());
+        for view in ressources.views.values() {
+            released = released.and(self.texture_manager.drop_texture_view(*view));
+        }
+        for sampler in ressources.samplers.values() {
+            released = released.and(self.texture_manager.drop_sampler(*sampler));
+        }
+        let (materials, meshes) = match result.and_then(|loaded| released.map(|()| loaded)) {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                // Frees everything created before the failure, which is the error reported.
+                for texture in ressources.textures.values() {
+                    self.texture_manager.drop_texture(*texture).ok();
+                }
+                for mesh in &ressources.meshes {
+                    self.mesh_manager.drop_mesh(*mesh).ok();
+                }
+                return Err(err);
+            }
+        };
+
+        let nodes = document
+            .nodes()
+            .map(|node| GltfNode {
+                name: node.name().map(String::from),
+                transform: node.transform().matrix().into(),
+                children: node.children().map(|child| child.index()).collect(),
+                mesh: node.mesh().map(|mesh| mesh.index()),
+            })
+            .collect();
+
+        let roots = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .map(|scene| scene.nodes().map(|node| node.index()).collect())
+            .unwrap_or_else(Vec::new);
+
+        Ok(GltfScene {
+            nodes,
+            roots,
+            meshes,
+            materials,
+        })
+    }
+
+    fn load_gltf_materials(
+        &mut self,
+        document: &::gltf::Document,
+        images: &[::gltf::image::Data],
+        ressources: &mut GltfRessources,
+    ) -> Result<Vec<GltfMaterial>> {
+        // glTF textures are an image and a sampler, both can be shared between textures.
+        let mut materials = Vec::new();
+        for material in document.materials() {
+            let pbr = material.pbr_metallic_roughness();
+            let base_color_texture = match pbr.base_color_texture() {
+                Some(info) => {
+                    let texture = info.texture();
+                    match ressources.textures.get(&texture.index()) {
+                        Some(texture_id) => Some(*texture_id),
+                        None => {
+                            let texture_manager = &mut self.texture_manager;
+                            let image = texture.source().index();
+                            let view = *ressources.views.entry(image).or_insert_with(|| {
+                                texture_manager
+                                    .create_texture_view_from_image(&image_to_rgba(&images[image]))
+                            });
+                            let gltf_sampler = texture.sampler();
+                            let sampler = *ressources
+                                .samplers
+                                .entry(gltf_sampler.index())
+                                .or_insert_with(|| {
+                                    texture_manager
+                                        .create_sampler(&sampler_descriptor(&gltf_sampler))
+                                });
+                            let texture_id = texture_manager.create_texture(view, sampler)?;
+                            ressources.textures.insert(texture.index(), texture_id);
+                            Some(texture_id)
+                        }
+                    }
+                }
+                None => None,
+            };
+
+            materials.push(GltfMaterial {
+                name: material.name().map(String::from),
+                base_color_factor: pbr.base_color_factor(),
+                base_color_texture,
+            });
+        }
+
+        Ok(materials)
+    }
+
+    fn load_gltf_meshes(
+        &mut self,
+        document: &::gltf::Document,
+        buffers: &[::gltf::buffer::Data],
+        ressources: &mut GltfRessources,
+    ) -> Result<Vec<GltfMesh>> {
+        let mut meshes = Vec::new();
+        for mesh in document.meshes() {
+            let mut primitives = Vec::new();
+            for primitive in mesh.primitives() {
+                let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+                let positions: Vec<[f32; 3]> = reader
+                    .read_positions()
+                    .ok_or(Error::MissingPositions {
+                        mesh: mesh.index(),
+                        primitive: primitive.index(),
+                    })?
+                    .collect();
+
+                let indices: Vec<u32> = match reader.read_indices() {
+                    Some(indices) => indices.into_u32().collect(),
+                    None => (0..positions.len() as u32).collect(),
+                };
+                let indices = triangle_list(primitive.mode(), &indices).ok_or(
+                    Error::UnsupportedPrimitiveMode {
+                        mesh: mesh.index(),
+                        primitive: primitive.index(),
+                        mode: primitive.mode(),
+                    },
+                )?;
+
+                let mut uvs = reader
+                    .read_tex_coords(0)
+                    .map(|uvs| uvs.into_f32().collect())
+                    .unwrap_or_else(Vec::new);
+                uvs.resize(positions.len(), [0.0, 0.0]);
+                let normals = match reader.read_normals() {
+                    Some(normals) => normals.collect(),
+                    None => compute_normals(&positions, &indices),
+                };
+                let tangents: Option<Vec<[f32; 4]>> =
+                    reader.read_tangents().map(|tangents| tangents.collect());
+                let colors: Option<Vec<[f32; 4]>> = reader
+                    .read_colors(0)
+                    .map(|colors| colors.into_rgba_f32().collect());
+                // Multiplied with the vertex colors so untextured materials get their color.
+                let base_color_factor = primitive
+                    .material()
+                    .pbr_metallic_roughness()
+                    .base_color_factor();
+
+                let vertices: Vec<LitVertex> = positions
+                    .iter()
+                    .zip(uvs.iter())
+                    .zip(normals.iter())
+                    .enumerate()
+                    .map(|(i, ((position, uv), normal))| {
+                        let mut vertex = LitVertex::new(*position, *uv, *normal);
+                        if let Some(tangent) =
+                            tangents.as_ref().and_then(|tangents| tangents.get(i))
+                        {
+                            vertex.tangent = *tangent;
+                        }
+                        let color = colors
+                            .as_ref()
+                            .and_then(|colors| colors.get(i))
+                            .unwrap_or(&[1.0; 4]);
+                        vertex.color = [0, 1, 2, 3].map(|c| color[c] * base_color_factor[c]);
+                        vertex
+                    })
+                    .collect();
+
+                let mesh_id = self.mesh_manager.create_mesh_indexed(&vertices, &indices)?;
+                ressources.meshes.push(mesh_id);
+
+                primitives.push(GltfPrimitive {
+                    mesh: mesh_id,
+                    material: primitive.material().index(),
+                });
+            }
+
+            meshes.push(GltfMesh {
+                name: mesh.name().map(String::from),
+                primitives,
+            });
+        }
+
+        Ok(meshes)
+    }
+}
@@ -0,0 +1,191 @@
+//! Typed generational handles.
+//!
+//! Every resource kind has its own handle type so a `SamplerId` can't be used where a
+//! `TextureViewId` is expected. Each slot keeps a generation counter, bumped when its
+//! resource is freed, so a handle kept after a drop is detected instead of silently
+//! referring to another resource.
+//!
+//! Resources are reference counted: each [`SlotMap::retain`] has to be matched by a
+//! [`SlotMap::release`], the resource is freed by the last release. Resources depending
+//! on others (like a texture using a view and a sampler) retain them to keep them alive.
+
+use crate::error::{Error, HandleKind, Result};
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// Resources stored behind a [`Handle`].
+pub trait Resource {
+    const KIND: HandleKind;
+}
+
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    /// Builds a handle from its parts, only meant for tests.
+    /// Nothing guarantees the handle is valid.
+    #[doc(hidden)]
+    pub fn from_raw_parts(index: u32, generation: u32) -> Self {
+        Self {
+            index,
+            generation,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+// Implemented by hand, deriving would require `T` to implement them too.
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T: Resource> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}({}v{})", T::KIND, self.index, self.generation)
+    }
+}
+
+struct Slot<T> {
+    generation: u32,
+    ref_count: u32,
+    value: Option<T>,
+}
+
+/// Storage handing out a [`Handle`] for each inserted value, freed slots are reused.
+pub struct SlotMap<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+}
+
+impl<T> Default for SlotMap<T> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+}
+
+impl<T: Resource> SlotMap<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, value: T) -> Handle<T> {
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.ref_count = 1;
+                slot.value = Some(value);
+                Handle::from_raw_parts(index, slot.generation)
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    ref_count: 1,
+                    value: Some(value),
+                });
+                Handle::from_raw_parts(self.slots.len() as u32 - 1, 0)
+            }
+        }
+    }
+
+    fn slot(&self, handle: Handle<T>) -> Result<&Slot<T>> {
+        let slot = self
+            .slots
+            .get(handle.index as usize)
+            .ok_or(Error::InvalidHandle {
+                kind: T::KIND,
+                index: handle.index,
+            })?;
+
+        if slot.generation != handle.generation || slot.value.is_none() {
+            return Err(Error::StaleHandle {
+                kind: T::KIND,
+                index: handle.index,
+                generation: handle.generation,
+            });
+        }
+        Ok(slot)
+    }
+
+    pub fn get(&self, handle: Handle<T>) -> Result<&T> {
+        Ok(self.slot(handle)?.value.as_ref().unwrap())
+    }
+
+    pub fn get_mut(&mut self, handle: Handle<T>) -> Result<&mut T> {
+        self.slot(handle)?;
+        Ok(self.slots[handle.index as usize].value.as_mut().unwrap())
+    }
+
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        self.slot(handle).is_ok()
+    }
+
+    /// Adds a reference to the value, it will need one more [`SlotMap::release`] to be freed.
+    pub fn retain(&mut self, handle: Handle<T>) -> Result<Handle<T>> {
+        self.slot(handle)?;
+        self.slots[handle.index as usize].ref_count += 1;
+        Ok(handle)
+    }
+
+    /// Removes a reference to the value. The value is returned when it was the last
+    /// reference, every existing handle to it then becomes stale.
+    pub fn release(&mut self, handle: Handle<T>) -> Result<Option<T>> {
+        self.slot(handle)?;
+
+        let slot = &mut self.slots[handle.index as usize];
+        slot.ref_count -= 1;
+        if slot.ref_count > 0 {
+            return Ok(None);
+        }
+
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+        Ok(slot.value.take())
+    }
+
+    pub fn ref_count(&self, handle: Handle<T>) -> Result<u32> {
+        Ok(self.slot(handle)?.ref_count)
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
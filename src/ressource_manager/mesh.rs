@@ -1,45 +1,87 @@
-use crate::geometry::Vertex;
+use super::handle::{Handle, Resource, SlotMap};
+use crate::error::{HandleKind, Result};
+use crate::geometry::{self, VertexLayout};
 
 use wgpu::util::DeviceExt;
 
-use std::collections::HashMap;
+use std::any::TypeId;
+use std::ops::Range;
 use std::sync::{Arc, Mutex};
 
-pub type MeshId = u32;
+pub type MeshId = Handle<Mesh>;
+
+/// Vertex type and index format of a mesh, both are part of the render pipeline state.
+pub(crate) type PipelineKey = (TypeId, wgpu::IndexFormat);
+
+/// Integer types usable as mesh indices.
+pub trait Index: bytemuck::Pod {
+    const FORMAT: wgpu::IndexFormat;
+}
+
+impl Index for u16 {
+    const FORMAT: wgpu::IndexFormat = wgpu::IndexFormat::Uint16;
+}
+
+/// Needed for meshes with more than 65536 vertices.
+impl Index for u32 {
+    const FORMAT: wgpu::IndexFormat = wgpu::IndexFormat::Uint32;
+}
 
 pub struct MeshManager {
     device: Arc<Mutex<wgpu::Device>>,
-    meshes: HashMap<MeshId, Mesh>,
-    mesh_id: MeshId,
+    meshes: SlotMap<Mesh>,
 }
 
 impl MeshManager {
     pub fn new(device: Arc<Mutex<wgpu::Device>>) -> Self {
         Self {
             device,
-            meshes: HashMap::new(),
-            mesh_id: 0,
+            meshes: SlotMap::new(),
         }
     }
 
-    pub(crate) fn draw_mesh<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, mesh_id: MeshId) {
-        let mesh = self
-            .meshes
-            .get(&mesh_id)
-            .unwrap_or_else(|| panic!("Invalid MeshId: {}", mesh_id));
+    /// Fails like [`MeshManager::draw_mesh`] would, before a render pass is started.
+    pub(crate) fn check_mesh(&self, mesh_id: MeshId) -> Result<()> {
+        self.meshes.get(mesh_id).map(drop)
+    }
+
+    pub(crate) fn draw_mesh<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        mesh_id: MeshId,
+        instances: Range<u32>,
+    ) -> Result<()> {
+        let mesh = self.meshes.get(mesh_id)?;
 
         render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
         match &mesh.index_buffer {
-            Some(index_buffer) => {
+            Some((index_buffer, _)) => {
                 render_pass.set_index_buffer(index_buffer.slice(..));
-                render_pass.draw_indexed(0..mesh.nb_vertices, 0, 0..1);
+                render_pass.draw_indexed(0..mesh.nb_vertices, 0, instances);
             }
-            None => render_pass.draw(0..mesh.nb_vertices, 0..1),
+            None => render_pass.draw(0..mesh.nb_vertices, instances),
         };
+        Ok(())
     }
 
-    pub fn create_mesh(&mut self, vertices: &[Vertex]) -> MeshId {
-        self.mesh_id += 1;
+    /// Key of the render pipeline able to draw the mesh, with the layout of its vertices.
+    /// Meshes without indices can be drawn by any pipeline of their vertex type.
+    pub(crate) fn pipeline_key(
+        &self,
+        mesh_id: MeshId,
+    ) -> Result<(PipelineKey, &wgpu::VertexBufferDescriptor<'static>)> {
+        let mesh = self.meshes.get(mesh_id)?;
+        let index_format = match &mesh.index_buffer {
+            Some((_, format)) => *format,
+            None => wgpu::IndexFormat::Uint16,
+        };
+        Ok(((mesh.vertex_type, index_format), &mesh.vertex_layout))
+    }
+
+    /// Fails if the built-in shaders can't read the vertex type, see
+    /// [`check_vertex_layout`](geometry::check_vertex_layout).
+    pub fn create_mesh<V: VertexLayout>(&mut self, vertices: &[V]) -> Result<MeshId> {
+        geometry::check_vertex_layout(&V::BUFFER_DESCRIPTOR)?;
         let nb_vertices = vertices.len() as u32;
 
         let device = self.device.lock().unwrap();
@@ -54,14 +96,20 @@ impl MeshManager {
             nb_vertices,
             vertex_buffer,
             index_buffer: None,
+            vertex_type: TypeId::of::<V>(),
+            vertex_layout: V::BUFFER_DESCRIPTOR,
         };
 
-        self.meshes.insert(self.mesh_id, mesh).unwrap();
-        self.mesh_id
+        Ok(self.meshes.insert(mesh))
     }
 
-    pub fn create_mesh_indexed(&mut self, vertices: &[Vertex], indices: &[u16]) -> MeshId {
-        self.mesh_id += 1;
+    /// Indices can either be `u16` or `u32`.
+    pub fn create_mesh_indexed<V: VertexLayout, I: Index>(
+        &mut self,
+        vertices: &[V],
+        indices: &[I],
+    ) -> Result<MeshId> {
+        geometry::check_vertex_layout(&V::BUFFER_DESCRIPTOR)?;
         let nb_vertices = indices.len() as u32;
 
         let device = self.device.lock().unwrap();
@@ -81,20 +129,40 @@ impl MeshManager {
         let mesh = Mesh {
             nb_vertices,
             vertex_buffer,
-            index_buffer: Some(index_buffer),
+            index_buffer: Some((index_buffer, I::FORMAT)),
+            vertex_type: TypeId::of::<V>(),
+            vertex_layout: V::BUFFER_DESCRIPTOR,
         };
 
-        self.meshes.insert(self.mesh_id, mesh);
-        self.mesh_id
+        Ok(self.meshes.insert(mesh))
+    }
+
+    /// Format of the index buffer of the mesh, `None` when it has no indices.
+    pub fn index_format(&self, mesh_id: MeshId) -> Result<Option<wgpu::IndexFormat>> {
+        let mesh = self.meshes.get(mesh_id)?;
+        Ok(mesh.index_buffer.as_ref().map(|(_, format)| *format))
     }
 
-    pub fn drop_mesh(&mut self, mesh_id: MeshId) {
-        self.meshes.remove(&mesh_id);
+    /// Adds a reference to the mesh, it has to be matched by a [`MeshManager::drop_mesh`].
+    pub fn retain_mesh(&mut self, mesh_id: MeshId) -> Result<MeshId> {
+        self.meshes.retain(mesh_id)
+    }
+
+    /// Releases a reference to the mesh, its buffers are freed with the last reference
+    /// and the handle becomes stale.
+    pub fn drop_mesh(&mut self, mesh_id: MeshId) -> Result<()> {
+        self.meshes.release(mesh_id).map(drop)
     }
 }
 
 pub struct Mesh {
     nb_vertices: u32,
     vertex_buffer: wgpu::Buffer,
-    index_buffer: Option<wgpu::Buffer>,
+    index_buffer: Option<(wgpu::Buffer, wgpu::IndexFormat)>,
+    vertex_type: TypeId,
+    vertex_layout: wgpu::VertexBufferDescriptor<'static>,
+}
+
+impl Resource for Mesh {
+    const KIND: HandleKind = HandleKind::Mesh;
 }
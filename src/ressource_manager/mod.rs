@@ -1,6 +1,16 @@
+pub mod atlas;
+pub mod cubemap;
+mod exr;
+pub mod format;
+pub mod gltf;
+pub mod handle;
 pub mod mesh;
+pub mod mipmap;
+pub mod obj;
 pub mod texture;
 
+use crate::error::Result;
+use crate::scene::Scene;
 use mesh::MeshManager;
 use texture::TextureManager;
 
@@ -21,4 +31,24 @@ impl RessourceManager {
             mesh_manager,
         }
     }
+
+    /// Checks the handles used by the scene and its offscreen passes, so a ressource
+    /// dropped after being submitted fails the frame before anything reaches the gpu.
+    pub fn check_scene(&self, scene: &Scene) -> Result<()> {
+        if let Some(skybox) = scene.skybox {
+            self.texture_manager.check_cubemap(skybox)?;
+        }
+        for item in scene.items() {
+            self.mesh_manager.check_mesh(item.mesh)?;
+            self.texture_manager.check_texture(item.texture)?;
+        }
+        for pass in &scene.texture_passes {
+            self.texture_manager.view(pass.target)?;
+            if let Some(depth_target) = pass.depth_target {
+                self.texture_manager.view(depth_target)?;
+            }
+            self.check_scene(&pass.scene)?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,229 @@
+//! Wavefront OBJ (+ MTL) loading.
+
+use super::mesh::MeshId;
+use super::texture::{SamplerId, TextureId};
+use super::RessourceManager;
+use crate::error::{Error, Result};
+use crate::geometry::{compute_normals, LitVertex};
+
+use std::path::Path;
+
+/// Geometry of one object or group of an OBJ file, before it is uploaded to the gpu.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjMeshData {
+    pub name: String,
+    pub positions: Vec<[f32; 3]>,
+    /// Empty when the object has no texture coordinates.
+    pub uvs: Vec<[f32; 2]>,
+    /// Empty when the object has no normals.
+    pub normals: Vec<[f32; 3]>,
+    pub indices: Vec<u32>,
+    /// Index of the material in the materials of the file.
+    pub material: Option<usize>,
+}
+
+impl ObjMeshData {
+    /// Vertices usable by the [`MeshManager`](super::mesh::MeshManager).
+    /// OBJ texture coordinates start from the bottom of the image, they are flipped here.
+    /// Objects without normals get smooth normals computed from their faces.
+    pub fn vertices(&self) -> Vec<LitVertex> {
+        let computed_normals;
+        let normals = if self.normals.len() == self.positions.len() {
+            &self.normals
+        } else {
+            computed_normals = compute_normals(&self.positions, &self.indices);
+            &computed_normals
+        };
+
+        self.positions
+            .iter()
+            .zip(normals)
+            .enumerate()
+            .map(|(i, (position, normal))| {
+                let uv = self
+                    .uvs
+                    .get(i)
+                    .map(|uv| [uv[0], 1.0 - uv[1]])
+                    .unwrap_or([0.0, 0.0]);
+                LitVertex::new(*position, uv, *normal)
+            })
+            .collect()
+    }
+}
+
+/// Loads the geometry of every object of an OBJ file along with its materials,
+/// without touching the gpu.
+pub fn parse_obj(path: impl AsRef<Path>) -> Result<(Vec<ObjMeshData>, Vec<tobj::Material>)> {
+    let (models, materials) = tobj::load_obj(
+        path.as_ref(),
+        &tobj::LoadOptions {
+            single_index: true,
+            triangulate: true,
+            ..Default::default()
+        },
+    )
+    .map_err(Error::Obj)?;
+    let materials = materials.map_err(Error::Mtl)?;
+
+    let meshes = models
+        .into_iter()
+        .map(|model| {
+            let mesh = model.mesh;
+            ObjMeshData {
+                name: model.name,
+                positions: mesh
+                    .positions
+                    .chunks_exact(3)
+                    .map(|p| [p[0], p[1], p[2]])
+                    .collect(),
+                uvs: mesh
+                    .texcoords
+                    .chunks_exact(2)
+                    .map(|uv| [uv[0], uv[1]])
+                    .collect(),
+                normals: mesh
+                    .normals
+                    .chunks_exact(3)
+                    .map(|n| [n[0], n[1], n[2]])
+                    .collect(),
+                indices: mesh.indices,
+                material: mesh.material_id,
+            }
+        })
+        .collect();
+
+    Ok((meshes, materials))
+}
+
+/// An OBJ file uploaded to the gpu.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjModel {
+    pub meshes: Vec<ObjMesh>,
+    pub materials: Vec<ObjMaterial>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjMesh {
+    pub name: String,
+    pub mesh: MeshId,
+    /// Index in [`ObjModel::materials`].
+    pub material: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjMaterial {
+    pub name: String,
+    pub diffuse_color: [f32; 3],
+    pub diffuse_texture: Option<TextureId>,
+}
+
+impl ObjModel {
+    /// Diffuse texture of the material used by a mesh, if any.
+    pub fn texture(&self, mesh: &ObjMesh) -> Option<TextureId> {
+        mesh.material
+            .and_then(|material| self.materials.get(material))
+            .and_then(|material| material.diffuse_texture)
+    }
+}
+
+impl RessourceManager {
+    /// Loads an OBJ file, its MTL materials and their diffuse textures.
+    /// Texture paths are resolved relatively to the OBJ file.
+    pub fn load_obj(&mut self, path: impl AsRef<Path>) -> Result<ObjModel> {
+        let path = path.as_ref();
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        let (meshes_data, materials_data) = parse_obj(path)?;
+
+        let mut sampler: Option<SamplerId> = None;
+        let mut materials = Vec::with_capacity(materials_data.len());
+        let mut result = Ok(());
+        for material in materials_data {
+            let diffuse_texture = if material.diffuse_texture.is_empty() {
+                None
+            } else {
+                let texture_path = directory.join(&material.diffuse_texture);
+                match self.load_obj_texture(&texture_path, &mut sampler) {
+                    Ok(texture) => Some(texture),
+                    Err(err) => {
+                        result = Err(err);
+                        break;
+                    }
+                }
+            };
+
+            materials.push(ObjMaterial {
+                name: material.name,
+                diffuse_color: material.diffuse,
+                diffuse_texture,
+            });
+        }
+
+        // The textures keep the shared sampler alive.
+        if let Some(sampler) = sampler {
+            result = result.and(self.texture_manager.drop_sampler(sampler));
+        }
+
+        let mut meshes = Vec::with_capacity(meshes_data.len());
+        if result.is_ok() {
+            for data in &meshes_data {
+                match self
+                    .mesh_manager
+                    .create_mesh_indexed(&data.vertices(), &data.indices)
+                {
+                    Ok(mesh) => meshes.push(ObjMesh {
+                        name: data.name.clone(),
+                        mesh,
+                        material: data.material,
+                    }),
+                    Err(err) => {
+                        result = Err(err);
+                        break;
+                    }
+                }
+            }
+        }
+
+        if let Err(err) = result {
+            // Frees everything created before the failure, which is the error reported.
+            for mesh in &meshes {
+                self.mesh_manager.drop_mesh(mesh.mesh).ok();
+            }
+            for texture in materials
+                .iter()
+                .filter_map(|material| material.diffuse_texture)
+            {
+                self.texture_manager.drop_texture(texture).ok();
+            }
+            return Err(err);
+        }
+
+        Ok(ObjModel { meshes, materials })
+    }
+
+    fn load_obj_texture(
+        &mut self,
+        texture_path: &Path,
+        sampler: &mut Option<SamplerId>,
+    ) -> Result<TextureId> {
+        let bytes = std::fs::read(texture_path)
+            .map_err(|err| Error::Io(texture_path.to_path_buf(), err))?;
+
+        let texture_manager = &mut self.texture_manager;
+        let view = texture_manager.create_texture_view(&bytes)?;
+        let sampler = *sampler.get_or_insert_with(|| {
+            texture_manager.create_sampler(&wgpu::SamplerDescriptor {
+                address_mode_u: wgpu::AddressMode::Repeat,
+                address_mode_v: wgpu::AddressMode::Repeat,
+                address_mode_w: wgpu::AddressMode::Repeat,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                mipmap_filter: wgpu::FilterMode::Linear,
+                ..Default::default()
+            })
+        });
+        let texture = texture_manager.create_texture(view, sampler);
+        // Dropped even on failure, the texture holds its own reference.
+        texture_manager.drop_texture_view(view)?;
+        texture
+    }
+}
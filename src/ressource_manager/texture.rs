@@ -1,143 +1,622 @@
-use std::collections::HashMap;
+use super::cubemap;
+use super::format::{self, TextureFormat, TextureImage};
+use super::handle::{Handle, Resource, SlotMap};
+use super::mipmap::{self, MipmapGenerator};
+use crate::error::{Error, HandleKind, Result};
+use crate::readback;
+
 use std::sync::{Arc, Mutex};
 
-pub type TextureViewId = u32;
-pub type SamplerId = u32;
-pub type TextureId = u32;
+pub type TextureViewId = Handle<TextureView>;
+pub type SamplerId = Handle<Sampler>;
+pub type TextureId = Handle<Texture>;
+pub type CubemapId = Handle<Cubemap>;
+
+/// A view on the whole texture, which is kept to allow updating its texels.
+pub struct TextureView {
+    view: wgpu::TextureView,
+    texture: wgpu::Texture,
+    size: (u32, u32),
+    format: TextureFormat,
+    mip_level_count: u32,
+}
+
+pub struct Sampler {
+    sampler: wgpu::Sampler,
+}
+
+/// A texture view and a sampler bound together, ready to be used by the shaders.
+/// Both are kept alive as long as the texture is.
+pub struct Texture {
+    bind_group: wgpu::BindGroup,
+    texture_view: TextureViewId,
+    sampler: SamplerId,
+}
+
+/// Six square faces sampled with a direction, bound with their own sampler.
+pub struct Cubemap {
+    bind_group: wgpu::BindGroup,
+}
+
+impl Resource for TextureView {
+    const KIND: HandleKind = HandleKind::TextureView;
+}
+
+impl Resource for Sampler {
+    const KIND: HandleKind = HandleKind::Sampler;
+}
+
+impl Resource for Texture {
+    const KIND: HandleKind = HandleKind::Texture;
+}
+
+impl Resource for Cubemap {
+    const KIND: HandleKind = HandleKind::Cubemap;
+}
 
 pub struct TextureManager {
     pub(crate) bind_group_layout: wgpu::BindGroupLayout,
+    pub(crate) cubemap_bind_group_layout: wgpu::BindGroupLayout,
     device: Arc<Mutex<wgpu::Device>>,
     queue: Arc<Mutex<wgpu::Queue>>,
-    texture_views: HashMap<TextureViewId, wgpu::TextureView>,
-    samplers: HashMap<SamplerId, wgpu::Sampler>,
-    textures: HashMap<TextureId, wgpu::BindGroup>,
-    texture_view_id: TextureViewId,
-    sampler_id: SamplerId,
-    texture_id: TextureId,
+    texture_views: SlotMap<TextureView>,
+    samplers: SlotMap<Sampler>,
+    textures: SlotMap<Texture>,
+    cubemaps: SlotMap<Cubemap>,
+    mipmap_generator: MipmapGenerator,
 }
 
 impl TextureManager {
     pub fn new(device: Arc<Mutex<wgpu::Device>>, queue: Arc<Mutex<wgpu::Queue>>) -> Self {
         let mutex_guard = device.lock().unwrap();
-        let bind_group_layout =
-            mutex_guard.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("Texture BindGroupLayout"),
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStage::FRAGMENT,
-                        ty: wgpu::BindingType::SampledTexture {
-                            dimension: wgpu::TextureViewDimension::D2,
-                            component_type: wgpu::TextureComponentType::Uint,
-                            multisampled: false,
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStage::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler { comparison: false },
-                        count: None,
-                    },
-                ],
-            });
+        // Every texture format is read as floats by the shaders.
+        let bind_group_layout = Self::create_bind_group_layout(
+            &mutex_guard,
+            "Texture BindGroupLayout",
+            wgpu::TextureViewDimension::D2,
+            wgpu::TextureComponentType::Float,
+        );
+        let cubemap_bind_group_layout = Self::create_bind_group_layout(
+            &mutex_guard,
+            "Cubemap BindGroupLayout",
+            wgpu::TextureViewDimension::Cube,
+            wgpu::TextureComponentType::Float,
+        );
+        let mipmap_generator = MipmapGenerator::new(&mutex_guard);
         drop(mutex_guard);
 
         Self {
             bind_group_layout,
+            cubemap_bind_group_layout,
             device,
             queue,
-            texture_views: HashMap::new(),
-            samplers: HashMap::new(),
-            textures: HashMap::new(),
-            texture_view_id: 0,
-            sampler_id: 0,
-            texture_id: 0,
+            texture_views: SlotMap::new(),
+            samplers: SlotMap::new(),
+            textures: SlotMap::new(),
+            cubemaps: SlotMap::new(),
+            mipmap_generator,
         }
     }
 
+    fn create_bind_group_layout(
+        device: &wgpu::Device,
+        label: &str,
+        dimension: wgpu::TextureViewDimension,
+        component_type: wgpu::TextureComponentType,
+    ) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some(label),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        dimension,
+                        component_type,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler { comparison: false },
+                    count: None,
+                },
+            ],
+        })
+    }
+
     pub(crate) fn bind_texture<'a>(
         &'a self,
         render_pass: &mut wgpu::RenderPass<'a>,
         texture: TextureId,
         idx: u32,
-    ) {
-        let bind_group = self
-            .textures
-            .get(&texture)
-            .unwrap_or_else(|| panic!("Invalid TextureId: {}", texture));
-
-        render_pass.set_bind_group(idx, bind_group, &[]);
-    }
-
-    pub fn create_texture_view(&mut self, bytes: &[u8]) -> TextureViewId {
-        self.texture_view_id += 1;
-        let image = image::load_from_memory(bytes).unwrap().to_rgba();
-        let dimension = image.dimensions();
-        let size = wgpu::Extent3d {
-            width: dimension.0,
-            height: dimension.1,
-            depth: 1,
+    ) -> Result<()> {
+        let texture = self.textures.get(texture)?;
+
+        render_pass.set_bind_group(idx, &texture.bind_group, &[]);
+        Ok(())
+    }
+
+    /// Fails like [`TextureManager::bind_texture`] would, before a render pass is started.
+    pub(crate) fn check_texture(&self, texture: TextureId) -> Result<()> {
+        self.textures.get(texture).map(drop)
+    }
+
+    pub(crate) fn check_cubemap(&self, cubemap: CubemapId) -> Result<()> {
+        self.cubemaps.get(cubemap).map(drop)
+    }
+
+    pub(crate) fn bind_cubemap<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        cubemap: CubemapId,
+        idx: u32,
+    ) -> Result<()> {
+        let cubemap = self.cubemaps.get(cubemap)?;
+
+        render_pass.set_bind_group(idx, &cubemap.bind_group, &[]);
+        Ok(())
+    }
+
+    /// Decodes an image into an sRGB texture view.
+    pub fn create_texture_view(&mut self, bytes: &[u8]) -> Result<TextureViewId> {
+        self.create_texture_view_with_format(bytes, TextureFormat::Rgba8Srgb)
+    }
+
+    /// Decodes an image into a texture view of the given format, `.hdr` files keep
+    /// their full range when loaded into float formats.
+    pub fn create_texture_view_with_format(
+        &mut self,
+        bytes: &[u8],
+        format: TextureFormat,
+    ) -> Result<TextureViewId> {
+        let image = TextureImage::decode(bytes)?;
+        self.create_texture_view_from_texture_image(&image, format)
+    }
+
+    /// Same as [`TextureManager::create_texture_view`] but from already decoded pixels.
+    pub fn create_texture_view_from_image(&mut self, image: &image::RgbaImage) -> TextureViewId {
+        let (width, height) = image.dimensions();
+        self.create_texture_view_from_bytes(width, height, TextureFormat::Rgba8Srgb, image)
+    }
+
+    /// Depth formats are an error, see [`TextureManager::create_depth_target`].
+    pub fn create_texture_view_from_texture_image(
+        &mut self,
+        image: &TextureImage,
+        format: TextureFormat,
+    ) -> Result<TextureViewId> {
+        check_upload_format(format)?;
+        let (width, height) = image.dimensions();
+        Ok(self.create_texture_view_from_bytes(width, height, format, &image.to_bytes(format)))
+    }
+
+    /// The whole mip chain is generated from the texels, unless the format can't be filtered.
+    fn create_texture_view_from_bytes(
+        &mut self,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        texels: &[u8],
+    ) -> TextureViewId {
+        let mip_level_count = if format.is_filterable() {
+            mipmap::mip_level_count(width, height)
+        } else {
+            1
         };
 
+        let texture = self.create_mipmapped_texture(width, height, mip_level_count, format);
+        self.write_region(&texture, 0, (0, 0), (width, height), format, texels);
+        self.generate_mip_chain(&texture, format, mip_level_count);
+
+        self.insert_view(texture, (width, height), format, mip_level_count)
+    }
+
+    /// Creates a texture view with undefined content, to be filled with
+    /// [`TextureManager::write_texture_view`]. It has a single mip level.
+    pub fn create_empty_texture_view(
+        &mut self,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+    ) -> TextureViewId {
+        let texture = self.create_mipmapped_texture(width, height, 1, format);
+        self.insert_view(texture, (width, height), format, 1)
+    }
+
+    /// Writes raw texels, in the layout of the format of the view, in a rectangle of its
+    /// first mip level. The other levels are left as is, see [`TextureManager::generate_mipmaps`].
+    pub fn write_texture_view(
+        &mut self,
+        texture_view: TextureViewId,
+        (x, y): (u32, u32),
+        (width, height): (u32, u32),
+        texels: &[u8],
+    ) -> Result<()> {
+        let view = self.texture_views.get(texture_view)?;
+        check_upload_format(view.format)?;
+        let fits =
+            |start: u32, len: u32, max: u32| start.checked_add(len).is_some_and(|end| end <= max);
+        if !fits(x, width, view.size.0) || !fits(y, height, view.size.1) {
+            return Err(Error::RegionOutOfBounds {
+                origin: (x, y),
+                size: (width, height),
+                texture_size: view.size,
+            });
+        }
+        let expected = view.format.bytes_per_pixel() as usize * width as usize * height as usize;
+        if texels.len() != expected {
+            return Err(Error::TexelDataSize {
+                expected,
+                found: texels.len(),
+            });
+        }
+
+        self.write_region(
+            &view.texture,
+            0,
+            (x, y),
+            (width, height),
+            view.format,
+            texels,
+        );
+        Ok(())
+    }
+
+    /// Regenerates every mip level of the view from the first one, once it has been
+    /// updated with [`TextureManager::write_texture_view`]. Pre-built levels are
+    /// overwritten, views of unfilterable formats are left as is.
+    pub fn generate_mipmaps(&mut self, texture_view: TextureViewId) -> Result<()> {
+        let view = self.texture_views.get(texture_view)?;
+        if view.mip_level_count > 1 && view.format.is_filterable() {
+            let device = self.device.lock().unwrap();
+            let queue = self.queue.lock().unwrap();
+            self.mipmap_generator.generate(
+                &device,
+                &queue,
+                &view.texture,
+                view.format.wgpu_format(),
+                view.mip_level_count,
+            );
+        }
+        Ok(())
+    }
+
+    /// Color target for [`Renderer::render_to_texture`](crate::Renderer::render_to_texture),
+    /// sampled like any other view once rendered. Its format can't be a depth format.
+    pub fn create_render_target(
+        &mut self,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+    ) -> TextureViewId {
+        self.create_empty_texture_view(width, height, format)
+    }
+
+    /// Depth target for [`Renderer::render_to_texture_with_depth`](crate::Renderer::render_to_texture_with_depth),
+    /// the depth of the scene can then be sampled, for shadow maps for example.
+    pub fn create_depth_target(&mut self, width: u32, height: u32) -> TextureViewId {
+        self.create_empty_texture_view(width, height, TextureFormat::Depth32Float)
+    }
+
+    pub(crate) fn view(&self, texture_view: TextureViewId) -> Result<&wgpu::TextureView> {
+        Ok(&self.texture_views.get(texture_view)?.view)
+    }
+
+    /// Copies the first mip level of the view back to the cpu, in the layout of its format.
+    /// This waits for the gpu to finish its work.
+    pub fn read_texture_view_texels(&self, texture_view: TextureViewId) -> Result<Vec<u8>> {
+        self.read_texture_view_level_texels(texture_view, 0)
+    }
+
+    /// Same as [`TextureManager::read_texture_view_texels`] for any mip level of the view.
+    pub fn read_texture_view_level_texels(
+        &self,
+        texture_view: TextureViewId,
+        level: u32,
+    ) -> Result<Vec<u8>> {
+        let view = self.texture_views.get(texture_view)?;
+        if level >= view.mip_level_count {
+            return Err(Error::MipLevelOutOfRange {
+                level,
+                mip_level_count: view.mip_level_count,
+            });
+        }
+
         let device = self.device.lock().unwrap();
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: None,
+        let queue = self.queue.lock().unwrap();
+        readback::read_texture(
+            &device,
+            &queue,
+            &view.texture,
+            level,
+            mipmap::mip_level_size(view.size.0, view.size.1, level),
+            view.format.bytes_per_pixel(),
+        )
+    }
+
+    pub fn texture_view_mip_level_count(&self, texture_view: TextureViewId) -> Result<u32> {
+        Ok(self.texture_views.get(texture_view)?.mip_level_count)
+    }
+
+    /// Same as [`TextureManager::read_texture_view_texels`] but converted to an 8 bits image,
+    /// float values are clamped to `0.0..=1.0`.
+    pub fn read_texture_view(&self, texture_view: TextureViewId) -> Result<image::RgbaImage> {
+        let texels = self.read_texture_view_texels(texture_view)?;
+        let view = self.texture_views.get(texture_view)?;
+        Ok(format::texels_to_rgba(view.format, view.size, &texels))
+    }
+
+    /// Size of the first mip level of the view.
+    pub fn texture_view_size(&self, texture_view: TextureViewId) -> Result<(u32, u32)> {
+        Ok(self.texture_views.get(texture_view)?.size)
+    }
+
+    pub fn texture_view_format(&self, texture_view: TextureViewId) -> Result<TextureFormat> {
+        Ok(self.texture_views.get(texture_view)?.format)
+    }
+
+    fn insert_view(
+        &mut self,
+        texture: wgpu::Texture,
+        size: (u32, u32),
+        format: TextureFormat,
+        mip_level_count: u32,
+    ) -> TextureViewId {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.texture_views.insert(TextureView {
+            view,
+            texture,
             size,
-            mip_level_count: 1,
+            format,
+            mip_level_count,
+        })
+    }
+
+    fn generate_mip_chain(
+        &mut self,
+        texture: &wgpu::Texture,
+        format: TextureFormat,
+        mip_level_count: u32,
+    ) {
+        let device = self.device.lock().unwrap();
+        let queue = self.queue.lock().unwrap();
+        self.mipmap_generator.generate(
+            &device,
+            &queue,
+            texture,
+            format.wgpu_format(),
+            mip_level_count,
+        );
+    }
+
+    /// Creates a view of the given format from pre-built mip levels, starting from the
+    /// full size image. Each level has to be half the size of the previous one, rounded
+    /// down, and there can't be more levels than in a full chain.
+    pub fn create_texture_view_with_mips(
+        &mut self,
+        levels: &[TextureImage],
+        format: TextureFormat,
+    ) -> Result<TextureViewId> {
+        check_upload_format(format)?;
+        let (width, height) = levels.first().ok_or(Error::NoMipLevels)?.dimensions();
+        let max = mipmap::mip_level_count(width, height);
+        if levels.len() as u32 > max {
+            return Err(Error::TooManyMipLevels {
+                count: levels.len() as u32,
+                max,
+            });
+        }
+        for (level, image) in levels.iter().enumerate() {
+            let expected = mipmap::mip_level_size(width, height, level as u32);
+            if image.dimensions() != expected {
+                return Err(Error::MipLevelSize {
+                    level: level as u32,
+                    expected,
+                    found: image.dimensions(),
+                });
+            }
+        }
+
+        let texture = self.create_mipmapped_texture(width, height, levels.len() as u32, format);
+        for (level, image) in levels.iter().enumerate() {
+            self.write_region(
+                &texture,
+                level as u32,
+                (0, 0),
+                image.dimensions(),
+                format,
+                &image.to_bytes(format),
+            );
+        }
+
+        Ok(self.insert_view(texture, (width, height), format, levels.len() as u32))
+    }
+
+    fn create_mipmapped_texture(
+        &self,
+        width: u32,
+        height: u32,
+        mip_level_count: u32,
+        format: TextureFormat,
+    ) -> wgpu::Texture {
+        let device = self.device.lock().unwrap();
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth: 1,
+            },
+            mip_level_count,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
-            usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
-        });
-        drop(device);
+            format: format.wgpu_format(),
+            // Rendering to the texture is needed to generate the mip chain.
+            usage: wgpu::TextureUsage::SAMPLED
+                | wgpu::TextureUsage::COPY_DST
+                | wgpu::TextureUsage::COPY_SRC
+                | wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        })
+    }
 
+    fn write_region(
+        &self,
+        texture: &wgpu::Texture,
+        level: u32,
+        (x, y): (u32, u32),
+        (width, height): (u32, u32),
+        format: TextureFormat,
+        texels: &[u8],
+    ) {
         let queue = self.queue.lock().unwrap();
         queue.write_texture(
             wgpu::TextureCopyView {
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
+                texture,
+                mip_level: level,
+                origin: wgpu::Origin3d { x, y, z: 0 },
             },
-            &image,
+            texels,
             wgpu::TextureDataLayout {
                 offset: 0,
-                bytes_per_row: 4 * dimension.0,
-                rows_per_image: dimension.1,
+                bytes_per_row: format.bytes_per_pixel() * width,
+                rows_per_image: height,
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth: 1,
             },
-            size,
         );
+    }
+
+    /// Creates a cubemap from its faces, in the order of [`CubeFace::ALL`](super::cubemap::CubeFace::ALL).
+    /// The faces have to be square and of the same size.
+    pub fn create_cubemap(
+        &mut self,
+        faces: &[TextureImage; 6],
+        format: TextureFormat,
+    ) -> Result<CubemapId> {
+        check_upload_format(format)?;
+        let (size, _) = faces[0].dimensions();
+        for (face, image) in faces.iter().enumerate() {
+            if image.dimensions() != (size, size) {
+                return Err(Error::CubemapFaceSize {
+                    face,
+                    expected: size,
+                    found: image.dimensions(),
+                });
+            }
+        }
+
+        let device = self.device.lock().unwrap();
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Cubemap"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth: 6,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: format.wgpu_format(),
+            usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+        });
+        drop(device);
+
+        let queue = self.queue.lock().unwrap();
+        for (layer, image) in faces.iter().enumerate() {
+            queue.write_texture(
+                wgpu::TextureCopyView {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: layer as u32,
+                    },
+                },
+                &image.to_bytes(format),
+                wgpu::TextureDataLayout {
+                    offset: 0,
+                    bytes_per_row: format.bytes_per_pixel() * size,
+                    rows_per_image: size,
+                },
+                wgpu::Extent3d {
+                    width: size,
+                    height: size,
+                    depth: 1,
+                },
+            );
+        }
         drop(queue);
 
-        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Cubemap view"),
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
 
-        self.texture_views.insert(self.texture_view_id, view);
-        self.texture_view_id
+        let device = self.device.lock().unwrap();
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Cubemap sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.cubemap_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+        drop(device);
+
+        Ok(self.cubemaps.insert(Cubemap { bind_group }))
+    }
+
+    /// Creates a cubemap from an equirectangular panorama, like the usual `.hdr` environment maps.
+    pub fn create_cubemap_from_equirectangular(
+        &mut self,
+        panorama: &TextureImage,
+        face_size: u32,
+        format: TextureFormat,
+    ) -> Result<CubemapId> {
+        let faces = cubemap::equirectangular_to_cubemap(panorama, face_size);
+        self.create_cubemap(&faces, format)
     }
 
     pub fn create_sampler(&mut self, desc: &wgpu::SamplerDescriptor) -> SamplerId {
         let device = self.device.lock().unwrap();
         let sampler = device.create_sampler(desc);
         drop(device);
-        self.sampler_id += 1;
-        self.samplers.insert(self.sampler_id, sampler);
-        self.sampler_id
-    }
-
-    pub fn create_texture(&mut self, texture_view: TextureViewId, sampler: SamplerId) -> TextureId {
-        self.texture_id += 1;
-        let view = self
-            .texture_views
-            .get(&texture_view)
-            .unwrap_or_else(|| panic!("Invalid TextureViewId: {}", texture_view));
-        let sampler = self
-            .samplers
-            .get(&sampler)
-            .unwrap_or_else(|| panic!("Invalid SamplerId: {}", sampler));
+        self.samplers.insert(Sampler { sampler })
+    }
+
+    /// The texture holds a reference to the view and the sampler, they can be dropped
+    /// right after this call and will be freed with the texture.
+    pub fn create_texture(
+        &mut self,
+        texture_view: TextureViewId,
+        sampler: SamplerId,
+    ) -> Result<TextureId> {
+        let view = &self.texture_views.get(texture_view)?.view;
+        let sampler_id = sampler;
+        let sampler = &self.samplers.get(sampler)?.sampler;
 
         let device = self.device.lock().unwrap();
-        let texture = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: None,
             layout: &self.bind_group_layout,
             entries: &[
@@ -152,19 +631,73 @@ impl TextureManager {
             ],
         });
         drop(device);
-        self.textures.insert(self.texture_id, texture);
-        self.texture_id
+
+        self.texture_views.retain(texture_view)?;
+        self.samplers.retain(sampler_id)?;
+        Ok(self.textures.insert(Texture {
+            bind_group,
+            texture_view,
+            sampler: sampler_id,
+        }))
+    }
+
+    /// Adds a reference to the view, it has to be matched by a
+    /// [`TextureManager::drop_texture_view`].
+    pub fn retain_texture_view(&mut self, texture_view: TextureViewId) -> Result<TextureViewId> {
+        self.texture_views.retain(texture_view)
+    }
+
+    pub fn retain_sampler(&mut self, sampler: SamplerId) -> Result<SamplerId> {
+        self.samplers.retain(sampler)
+    }
+
+    pub fn retain_texture(&mut self, texture: TextureId) -> Result<TextureId> {
+        self.textures.retain(texture)
+    }
+
+    pub fn retain_cubemap(&mut self, cubemap: CubemapId) -> Result<CubemapId> {
+        self.cubemaps.retain(cubemap)
+    }
+
+    /// Releases a reference to the view, it is freed once no handle nor texture uses it.
+    pub fn drop_texture_view(&mut self, texture_view: TextureViewId) -> Result<()> {
+        self.texture_views.release(texture_view).map(drop)
     }
 
-    pub fn drop_texture_view(&mut self, texture_view: TextureViewId) {
-        self.texture_views.remove(&texture_view);
+    pub fn drop_sampler(&mut self, sampler: SamplerId) -> Result<()> {
+        self.samplers.release(sampler).map(drop)
+    }
+
+    /// Releases a reference to the texture, freeing it releases its view and sampler.
+    pub fn drop_texture(&mut self, texture: TextureId) -> Result<()> {
+        if let Some(texture) = self.textures.release(texture)? {
+            // Both are released even if the first one fails.
+            let view = self.texture_views.release(texture.texture_view);
+            let sampler = self.samplers.release(texture.sampler);
+            view?;
+            sampler?;
+        }
+        Ok(())
     }
 
-    pub fn drop_sampler(&mut self, sampler: SamplerId) {
-        self.samplers.remove(&sampler);
+    pub fn drop_cubemap(&mut self, cubemap: CubemapId) -> Result<()> {
+        self.cubemaps.release(cubemap).map(drop)
     }
 
-    pub fn drop_texture(&mut self, texture: TextureId) {
-        self.textures.remove(&texture);
+    /// Number of live references to a view, textures using it included.
+    pub fn texture_view_ref_count(&self, texture_view: TextureViewId) -> Result<u32> {
+        self.texture_views.ref_count(texture_view)
+    }
+
+    pub fn sampler_ref_count(&self, sampler: SamplerId) -> Result<u32> {
+        self.samplers.ref_count(sampler)
+    }
+}
+
+/// Depth textures can only be rendered to, wgpu forbids copying texels into them.
+fn check_upload_format(format: TextureFormat) -> Result<()> {
+    if format.is_depth() {
+        return Err(Error::TextureUploadFormat(format));
     }
+    Ok(())
 }
@@ -0,0 +1,351 @@
+use crate::camera::Camera;
+use crate::geometry::Instance;
+use crate::ressource_manager::mesh::MeshId;
+use crate::ressource_manager::texture::{CubemapId, TextureId, TextureViewId};
+
+use cgmath::{Matrix4, One, Quaternion, Rotation3, SquareMatrix, Vector3};
+
+use std::num::NonZeroU64;
+use std::ops::Range;
+
+/// Position, rotation and scale of a draw item.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub translation: Vector3<f32>,
+    pub rotation: Quaternion<f32>,
+    pub scale: Vector3<f32>,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: Vector3::new(0.0, 0.0, 0.0),
+            rotation: Quaternion::one(),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl Transform {
+    pub fn from_translation(translation: Vector3<f32>) -> Self {
+        Self {
+            translation,
+            ..Self::default()
+        }
+    }
+
+    pub fn from_rotation(axis: Vector3<f32>, angle: impl Into<cgmath::Rad<f32>>) -> Self {
+        Self {
+            rotation: Quaternion::from_axis_angle(axis, angle),
+            ..Self::default()
+        }
+    }
+
+    pub fn from_scale(scale: f32) -> Self {
+        Self {
+            scale: Vector3::new(scale, scale, scale),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_translation(mut self, translation: Vector3<f32>) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: Quaternion<f32>) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Vector3<f32>) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Model matrix applying the scale, then the rotation, then the translation.
+    pub fn matrix(&self) -> Matrix4<f32> {
+        Matrix4::from_translation(self.translation)
+            * Matrix4::from(self.rotation)
+            * Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+}
+
+impl From<Transform> for Matrix4<f32> {
+    fn from(transform: Transform) -> Self {
+        transform.matrix()
+    }
+}
+
+/// A mesh drawn with a texture at a given place.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DrawItem {
+    pub mesh: MeshId,
+    pub texture: TextureId,
+    pub transform: Matrix4<f32>,
+}
+
+/// Draw items submitted for the next frame, seen through `camera`.
+/// The draw items are removed once the frame is rendered, the camera is kept.
+#[derive(Debug, Default)]
+pub struct Scene {
+    pub camera: Camera,
+    /// Drawn behind everything else, it is kept between frames like the camera.
+    pub skybox: Option<CubemapId>,
+    items: Vec<DrawItem>,
+    // Range of `instances` used by each item.
+    instance_ranges: Vec<Range<u32>>,
+    instances: Vec<Instance>,
+    pub(crate) texture_passes: Vec<TexturePass>,
+}
+
+/// A scene rendered into a texture before the scene it was submitted to.
+#[derive(Debug)]
+pub(crate) struct TexturePass {
+    pub(crate) target: TextureViewId,
+    pub(crate) depth_target: Option<TextureViewId>,
+    pub(crate) scene: Scene,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Submits a single instance of the item.
+    pub fn submit(&mut self, item: DrawItem) {
+        self.submit_instanced(item, &[Instance::default()]);
+    }
+
+    /// Submits the item once for every given instance, all of them are drawn in a single
+    /// draw call. The instance transforms are applied before the item transform.
+    pub fn submit_instanced(&mut self, item: DrawItem, instances: &[Instance]) {
+        let start = self.instances.len() as u32;
+        self.instances.extend_from_slice(instances);
+        self.instance_ranges
+            .push(start..start + instances.len() as u32);
+        self.items.push(item);
+    }
+
+    /// Shortcut to submit a mesh drawn in its own coordinates.
+    pub fn draw(&mut self, mesh: MeshId, texture: TextureId) {
+        self.submit(DrawItem {
+            mesh,
+            texture,
+            transform: Matrix4::identity(),
+        });
+    }
+
+    /// Submits a mesh drawn with the given model transform, the same mesh can be
+    /// submitted as many times as needed.
+    pub fn draw_transformed(
+        &mut self,
+        mesh: MeshId,
+        texture: TextureId,
+        transform: impl Into<Matrix4<f32>>,
+    ) {
+        self.submit(DrawItem {
+            mesh,
+            texture,
+            transform: transform.into(),
+        });
+    }
+
+    /// Shortcut to submit many instances of a mesh in one draw call.
+    pub fn draw_instanced(&mut self, mesh: MeshId, texture: TextureId, instances: &[Instance]) {
+        self.submit_instanced(
+            DrawItem {
+                mesh,
+                texture,
+                transform: Matrix4::identity(),
+            },
+            instances,
+        );
+    }
+
+    pub fn items(&self) -> &[DrawItem] {
+        &self.items
+    }
+
+    /// Items along with the range of instances they use in [`Scene::instances`].
+    pub fn instanced_items(&self) -> impl Iterator<Item = (&DrawItem, Range<u32>)> {
+        self.items.iter().zip(self.instance_ranges.iter().cloned())
+    }
+
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    /// Renders another scene into a render target before this one, see
+    /// [`Renderer::render_to_texture`](crate::Renderer::render_to_texture).
+    /// The offscreen passes of that scene are rendered before it.
+    pub fn render_to_texture(&mut self, target: TextureViewId, scene: Scene) {
+        self.texture_passes.push(TexturePass {
+            target,
+            depth_target: None,
+            scene,
+        });
+    }
+
+    /// Same as [`Scene::render_to_texture`] but the depth is kept in `depth_target`, see
+    /// [`Renderer::render_to_texture_with_depth`](crate::Renderer::render_to_texture_with_depth).
+    pub fn render_to_texture_with_depth(
+        &mut self,
+        target: TextureViewId,
+        depth_target: TextureViewId,
+        scene: Scene,
+    ) {
+        self.texture_passes.push(TexturePass {
+            target,
+            depth_target: Some(depth_target),
+            scene,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.instance_ranges.clear();
+        self.instances.clear();
+        self.texture_passes.clear();
+    }
+}
+
+const MODEL_SIZE: wgpu::BufferAddress = std::mem::size_of::<[f32; 16]>() as wgpu::BufferAddress;
+// Dynamic offsets have to be aligned on BIND_BUFFER_ALIGNMENT.
+const MODEL_STRIDE: wgpu::BufferAddress = wgpu::BIND_BUFFER_ALIGNMENT;
+
+/// Uniform buffer holding the model matrix of every draw item of a frame,
+/// each one is selected with a dynamic offset.
+pub(crate) struct ModelBuffer {
+    pub(crate) bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    buffer: wgpu::Buffer,
+    capacity: usize,
+}
+
+impl ModelBuffer {
+    pub(crate) fn new(device: &wgpu::Device) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Model BindGroupLayout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStage::VERTEX,
+                ty: wgpu::BindingType::UniformBuffer {
+                    dynamic: true,
+                    min_binding_size: NonZeroU64::new(MODEL_SIZE),
+                },
+                count: None,
+            }],
+        });
+
+        let capacity = 16;
+        let (buffer, bind_group) = Self::create_buffer(device, &bind_group_layout, capacity);
+
+        Self {
+            bind_group_layout,
+            bind_group,
+            buffer,
+            capacity,
+        }
+    }
+
+    fn create_buffer(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        capacity: usize,
+    ) -> (wgpu::Buffer, wgpu::BindGroup) {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Model buffer"),
+            size: MODEL_STRIDE * capacity as wgpu::BufferAddress,
+            usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Model BindGroup"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(buffer.slice(..MODEL_SIZE)),
+            }],
+        });
+
+        (buffer, bind_group)
+    }
+
+    /// Uploads the model matrices of the scene, growing the buffer if needed.
+    pub(crate) fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, scene: &Scene) {
+        let items = scene.items();
+        if items.len() > self.capacity {
+            self.capacity = items.len().next_power_of_two();
+            let (buffer, bind_group) =
+                Self::create_buffer(device, &self.bind_group_layout, self.capacity);
+            self.buffer = buffer;
+            self.bind_group = bind_group;
+        }
+
+        let mut data = vec![0u8; MODEL_STRIDE as usize * items.len()];
+        for (item, chunk) in items.iter().zip(data.chunks_mut(MODEL_STRIDE as usize)) {
+            let matrix: &[f32; 16] = item.transform.as_ref();
+            chunk[..MODEL_SIZE as usize].copy_from_slice(bytemuck::cast_slice(matrix));
+        }
+
+        if !data.is_empty() {
+            queue.write_buffer(&self.buffer, 0, &data);
+        }
+    }
+
+    /// Binds the model matrix of the `index`-th draw item of the uploaded scene.
+    pub(crate) fn bind<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        index: usize,
+        idx: u32,
+    ) {
+        let offset = (MODEL_STRIDE * index as wgpu::BufferAddress) as wgpu::DynamicOffset;
+        render_pass.set_bind_group(idx, &self.bind_group, &[offset]);
+    }
+}
+
+/// Vertex buffer holding the instances of every draw item of a frame.
+pub(crate) struct InstanceBuffer {
+    buffer: wgpu::Buffer,
+    capacity: usize,
+}
+
+impl InstanceBuffer {
+    pub(crate) fn new(device: &wgpu::Device) -> Self {
+        let capacity = 64;
+
+        Self {
+            buffer: Self::create_buffer(device, capacity),
+            capacity,
+        }
+    }
+
+    fn create_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Instance buffer"),
+            size: (std::mem::size_of::<Instance>() * capacity) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Uploads the instances of the scene, growing the buffer if needed.
+    pub(crate) fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, scene: &Scene) {
+        let instances = scene.instances();
+        if instances.len() > self.capacity {
+            self.capacity = instances.len().next_power_of_two();
+            self.buffer = Self::create_buffer(device, self.capacity);
+        }
+
+        if !instances.is_empty() {
+            queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(instances));
+        }
+    }
+
+    pub(crate) fn bind<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, slot: u32) {
+        render_pass.set_vertex_buffer(slot, self.buffer.slice(..));
+    }
+}
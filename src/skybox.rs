@@ -0,0 +1,168 @@
+use crate::camera::Camera;
+use crate::depth::DepthSettings;
+
+use std::collections::HashMap;
+use std::num::NonZeroU64;
+
+const SKYBOX_MATRIX_SIZE: wgpu::BufferAddress =
+    std::mem::size_of::<[f32; 16]>() as wgpu::BufferAddress;
+
+/// Draws a cubemap behind the scene, only the rotation of the camera is taken into account.
+pub(crate) struct SkyboxPass {
+    bind_group: wgpu::BindGroup,
+    buffer: wgpu::Buffer,
+    pipeline_layout: wgpu::PipelineLayout,
+    vs_module: wgpu::ShaderModule,
+    fs_module: wgpu::ShaderModule,
+    /// Pipelines by color format, created the first time a target of the format is used.
+    pipelines: HashMap<wgpu::TextureFormat, wgpu::RenderPipeline>,
+}
+
+impl SkyboxPass {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        cubemap_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Skybox BindGroupLayout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStage::VERTEX,
+                ty: wgpu::BindingType::UniformBuffer {
+                    dynamic: false,
+                    min_binding_size: NonZeroU64::new(SKYBOX_MATRIX_SIZE),
+                },
+                count: None,
+            }],
+        });
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Skybox buffer"),
+            size: SKYBOX_MATRIX_SIZE,
+            usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Skybox BindGroup"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(buffer.slice(..)),
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("skybox_pipeline_layout"),
+            bind_group_layouts: &[cubemap_bind_group_layout, &bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let vs_module = device.create_shader_module(wgpu::include_spirv!("../skybox.vert.spv"));
+        let fs_module = device.create_shader_module(wgpu::include_spirv!("../skybox.frag.spv"));
+
+        Self {
+            bind_group,
+            buffer,
+            pipeline_layout,
+            vs_module,
+            fs_module,
+            pipelines: HashMap::new(),
+        }
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        vs_module: &wgpu::ShaderModule,
+        fs_module: &wgpu::ShaderModule,
+        color_format: wgpu::TextureFormat,
+        depth_settings: &DepthSettings,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("skybox_pipeline"),
+            layout: Some(layout),
+            vertex_stage: wgpu::ProgrammableStageDescriptor {
+                module: vs_module,
+                entry_point: "main",
+            },
+            fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+                module: fs_module,
+                entry_point: "main",
+            }),
+            rasterization_state: Some(wgpu::RasterizationStateDescriptor::default()),
+            primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+            color_states: &[wgpu::ColorStateDescriptor {
+                format: color_format,
+                alpha_blend: wgpu::BlendDescriptor::REPLACE,
+                color_blend: wgpu::BlendDescriptor::REPLACE,
+                write_mask: wgpu::ColorWrite::ALL,
+            }],
+            // Drawn first and without touching the depth buffer, so the scene always
+            // covers it whatever the depth settings are.
+            depth_stencil_state: Some(wgpu::DepthStencilStateDescriptor {
+                format: depth_settings.format(),
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilStateDescriptor::default(),
+            }),
+            vertex_state: wgpu::VertexStateDescriptor {
+                index_format: wgpu::IndexFormat::Uint16,
+                vertex_buffers: &[],
+            },
+            sample_count: 1,
+            sample_mask: !0,
+            alpha_to_coverage_enabled: false,
+        })
+    }
+
+    /// The depth format is part of the pipelines, they have to be recreated when the
+    /// depth settings change.
+    pub(crate) fn clear_pipelines(&mut self) {
+        self.pipelines.clear();
+    }
+
+    pub(crate) fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        color_format: wgpu::TextureFormat,
+        depth_settings: &DepthSettings,
+    ) {
+        let Self {
+            pipeline_layout,
+            vs_module,
+            fs_module,
+            pipelines,
+            ..
+        } = self;
+
+        pipelines.entry(color_format).or_insert_with(|| {
+            Self::create_pipeline(
+                device,
+                pipeline_layout,
+                vs_module,
+                fs_module,
+                color_format,
+                depth_settings,
+            )
+        });
+    }
+
+    pub(crate) fn upload(&self, queue: &wgpu::Queue, camera: &Camera) {
+        let skybox_matrix = camera.skybox_matrix();
+        let matrix: &[f32; 16] = skybox_matrix.as_ref();
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(matrix));
+    }
+
+    /// The cubemap has to be bound to the set 0 and the pipeline of the color format
+    /// prepared beforehand.
+    pub(crate) fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        color_format: wgpu::TextureFormat,
+    ) {
+        render_pass.set_pipeline(&self.pipelines[&color_format]);
+        render_pass.set_bind_group(1, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
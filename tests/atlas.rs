@@ -0,0 +1,70 @@
+use image::{Rgba, RgbaImage};
+use wgpu_renderer::ressource_manager::atlas::{build_atlas_image, pack, AtlasRegion};
+use wgpu_renderer::UvRect;
+
+fn overlap(a: &AtlasRegion, b: &AtlasRegion, padding: u32) -> bool {
+    a.x < b.x + b.width + padding
+        && b.x < a.x + a.width + padding
+        && a.y < b.y + b.height + padding
+        && b.y < a.y + a.height + padding
+}
+
+#[test]
+fn packed_regions_fit_without_overlapping() {
+    let sizes = [
+        (32, 32),
+        (10, 50),
+        (64, 8),
+        (5, 5),
+        (20, 20),
+        (64, 64),
+        (1, 1),
+    ];
+    let padding = 2;
+    let layout = pack(&sizes, padding);
+
+    assert!(layout.width.is_power_of_two());
+    for (region, size) in layout.regions.iter().zip(sizes.iter()) {
+        assert_eq!((region.width, region.height), *size);
+        assert!(region.x >= padding && region.x + region.width + padding <= layout.width);
+        assert!(region.y >= padding && region.y + region.height + padding <= layout.height);
+    }
+    for (i, a) in layout.regions.iter().enumerate() {
+        for b in &layout.regions[i + 1..] {
+            assert!(!overlap(a, b, padding), "{:?} overlaps {:?}", a, b);
+        }
+    }
+}
+
+#[test]
+fn padding_repeats_the_borders() {
+    let red = RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 255]));
+    let blue = RgbaImage::from_pixel(2, 2, Rgba([0, 0, 255, 255]));
+    let layout = pack(&[(2, 2), (2, 2)], 1);
+    let atlas = build_atlas_image(&[red, blue], &layout, 1);
+
+    let region = layout.regions[1];
+    assert_eq!(
+        atlas.get_pixel(region.x - 1, region.y - 1),
+        &Rgba([0, 0, 255, 255])
+    );
+    assert_eq!(
+        atlas.get_pixel(region.x + region.width, region.y),
+        &Rgba([0, 0, 255, 255])
+    );
+}
+
+#[test]
+fn uv_rects_map_texture_coordinates() {
+    let layout = pack(&[(8, 4)], 0);
+    let rect = layout.uv_rect(layout.regions[0]);
+    assert_eq!(rect.min, [0.0, 0.0]);
+    assert_eq!(rect.max, [1.0, 1.0]);
+
+    let rect = UvRect {
+        min: [0.5, 0.25],
+        max: [1.0, 0.75],
+    };
+    assert_eq!(rect.map([0.0, 0.0]), [0.5, 0.25]);
+    assert_eq!(rect.map([0.5, 1.0]), [0.75, 0.75]);
+}
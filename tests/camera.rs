@@ -0,0 +1,82 @@
+use cgmath::{Deg, Point3, Vector4};
+use wgpu_renderer::camera::Camera;
+
+fn project(camera: &Camera, point: [f32; 3]) -> [f32; 3] {
+    let clip = camera.view_projection() * Vector4::new(point[0], point[1], point[2], 1.0);
+    [clip.x / clip.w, clip.y / clip.w, clip.z / clip.w]
+}
+
+fn assert_close(actual: [f32; 3], expected: [f32; 3]) {
+    for (a, e) in actual.iter().zip(expected.iter()) {
+        assert!((a - e).abs() < 1e-5, "{:?} != {:?}", actual, expected);
+    }
+}
+
+#[test]
+fn default_camera_keeps_normalized_coordinates() {
+    let camera = Camera::default();
+
+    assert_close(project(&camera, [0.0, 0.0, 0.0]), [0.0, 0.0, 0.5]);
+    assert_close(project(&camera, [1.0, -1.0, 0.0]), [1.0, -1.0, 0.5]);
+}
+
+#[test]
+fn orthographic_width_follows_aspect() {
+    let camera = Camera {
+        aspect: 2.0,
+        ..Camera::default()
+    };
+
+    assert_close(project(&camera, [2.0, 1.0, 0.0]), [1.0, 1.0, 0.5]);
+}
+
+#[test]
+fn perspective_depth_is_in_wgpu_range() {
+    let mut camera = Camera::perspective(
+        Point3::new(0.0, 0.0, 5.0),
+        Point3::new(0.0, 0.0, 0.0),
+        Deg(45.0),
+    );
+    camera.aspect = 16.0 / 9.0;
+
+    let target = project(&camera, [0.0, 0.0, 0.0]);
+    assert_close([target[0], target[1], 0.0], [0.0, 0.0, 0.0]);
+    assert!(target[2] > 0.0 && target[2] < 1.0);
+
+    assert_close(project(&camera, [0.0, 0.0, 4.9]), [0.0, 0.0, 0.0]);
+    assert_close(project(&camera, [0.0, 0.0, -95.0]), [0.0, 0.0, 1.0]);
+}
+
+#[test]
+fn skybox_matrix_ignores_the_camera_position() {
+    let camera = Camera::perspective(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(0.0, 0.0, -1.0),
+        Deg(90.0),
+    );
+    let moved = Camera::perspective(
+        Point3::new(5.0, 2.0, 3.0),
+        Point3::new(5.0, 2.0, 2.0),
+        Deg(90.0),
+    );
+    assert_eq!(camera.skybox_matrix(), moved.skybox_matrix());
+
+    // The center of the screen looks toward the target.
+    let far = camera.skybox_matrix() * Vector4::new(0.0, 0.0, 1.0, 1.0);
+    let direction = [far.x / far.w, far.y / far.w, far.z / far.w];
+    assert_close(
+        [direction[0], direction[1], direction[2].signum()],
+        [0.0, 0.0, -1.0],
+    );
+}
+
+#[test]
+fn null_viewport_keeps_the_aspect_ratio() {
+    let mut camera = Camera::default();
+    camera.set_viewport_size(200, 100);
+    assert_eq!(camera.aspect, 2.0);
+
+    camera.set_viewport_size(0, 0);
+    camera.set_viewport_size(200, 0);
+    assert_eq!(camera.aspect, 2.0);
+}
@@ -0,0 +1,22 @@
+mod common;
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn captured_frame_matches_the_rendered_one() {
+    let mut renderer = common::headless_renderer(64, 64);
+    let (mesh, texture) = common::textured_quad(renderer.ressource_manager_mut());
+
+    renderer.scene_mut().draw(mesh, texture);
+    renderer.render().unwrap();
+    assert!(renderer.take_captured_frame().is_none());
+
+    renderer.capture_next_frame();
+    renderer.scene_mut().draw(mesh, texture);
+    renderer.render().unwrap();
+    let captured = renderer.take_captured_frame().unwrap();
+    assert_eq!(captured, renderer.read_frame().unwrap().unwrap());
+
+    // Only the requested frame is captured.
+    renderer.render().unwrap();
+    assert!(renderer.take_captured_frame().is_none());
+}
@@ -0,0 +1,198 @@
+//! Golden image and handle helpers shared by the integration tests.
+//!
+//! Rendered frames are compared pixel by pixel against the reference PNGs stored in
+//! `tests/golden`. When a comparison fails, the rendered frame and a diff image are
+//! written in `target/golden` so the regression can be looked at.
+//! Set `UPDATE_GOLDEN=1` to (re)write the references from the current renderer output.
+
+#![allow(dead_code)]
+
+use image::{Rgba, RgbaImage};
+use wgpu_renderer::ressource_manager::handle::Handle;
+use wgpu_renderer::ressource_manager::mesh::MeshId;
+use wgpu_renderer::ressource_manager::texture::TextureId;
+use wgpu_renderer::ressource_manager::RessourceManager;
+use wgpu_renderer::{Renderer, Vertex};
+
+use std::path::PathBuf;
+
+/// Handle of a mesh that doesn't need to exist, for tests that never touch the gpu.
+pub fn mesh(index: u32) -> MeshId {
+    Handle::from_raw_parts(index, 0)
+}
+
+/// Handle of a texture that doesn't need to exist, for tests that never touch the gpu.
+pub fn texture(index: u32) -> TextureId {
+    Handle::from_raw_parts(index, 0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// Maximum difference allowed on each channel before a pixel is considered different.
+    pub channel: u8,
+    /// Fraction (between 0 and 1) of the pixels allowed to be different.
+    pub pixels: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            channel: 2,
+            pixels: 0.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Comparison {
+    pub mismatched_pixels: u32,
+    pub max_channel_delta: u8,
+    /// Mismatched pixels are red, others are a dimmed version of the expected image.
+    pub diff: RgbaImage,
+}
+
+impl Comparison {
+    pub fn passes(&self, tolerance: Tolerance) -> bool {
+        let total = (self.diff.width() * self.diff.height()).max(1) as f32;
+        self.mismatched_pixels as f32 / total <= tolerance.pixels
+    }
+}
+
+/// Compares two images of the same size, returns `None` if their sizes differ.
+pub fn compare(
+    actual: &RgbaImage,
+    expected: &RgbaImage,
+    tolerance: Tolerance,
+) -> Option<Comparison> {
+    if actual.dimensions() != expected.dimensions() {
+        return None;
+    }
+
+    let mut mismatched_pixels = 0;
+    let mut max_channel_delta = 0;
+    let mut diff = RgbaImage::new(expected.width(), expected.height());
+
+    for ((a, e), d) in actual
+        .pixels()
+        .zip(expected.pixels())
+        .zip(diff.pixels_mut())
+    {
+        let delta =
+            a.0.iter()
+                .zip(e.0.iter())
+                .map(|(a, e)| (*a as i16 - *e as i16).unsigned_abs() as u8)
+                .max()
+                .unwrap_or(0);
+        max_channel_delta = max_channel_delta.max(delta);
+
+        *d = if delta > tolerance.channel {
+            mismatched_pixels += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            Rgba([e[0] / 4, e[1] / 4, e[2] / 4, 255])
+        };
+    }
+
+    Some(Comparison {
+        mismatched_pixels,
+        max_channel_delta,
+        diff,
+    })
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.png", name))
+}
+
+fn output_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/golden")
+}
+
+/// Creates a headless renderer. Tests using it are `#[ignore]`d since they need a gpu,
+/// run them with `cargo test -- --ignored`.
+pub fn headless_renderer(width: u32, height: u32) -> Renderer {
+    futures::executor::block_on(Renderer::create_headless(width, height))
+        .unwrap_or_else(|err| panic!("{}, gpu tests can't run on this machine", err))
+}
+
+/// Creates a quad covering half of the screen textured with `happy-tree.png`.
+pub fn textured_quad(ressource_manager: &mut RessourceManager) -> (MeshId, TextureId) {
+    let vertices = &[
+        Vertex::new([-0.5, -0.5, 0.0], [0.0, 1.0]),
+        Vertex::new([0.5, -0.5, 0.0], [1.0, 1.0]),
+        Vertex::new([0.5, 0.5, 0.0], [1.0, 0.0]),
+        Vertex::new([-0.5, 0.5, 0.0], [0.0, 0.0]),
+    ];
+    let mesh = ressource_manager
+        .mesh_manager
+        .create_mesh_indexed(vertices, &[0u16, 1, 2, 0, 2, 3])
+        .unwrap();
+
+    let texture_manager = &mut ressource_manager.texture_manager;
+    let view = texture_manager
+        .create_texture_view(include_bytes!("../../happy-tree.png"))
+        .unwrap();
+    let sampler = texture_manager.create_sampler(&wgpu::SamplerDescriptor {
+        mag_filter: wgpu::FilterMode::Nearest,
+        min_filter: wgpu::FilterMode::Nearest,
+        ..Default::default()
+    });
+    let texture = texture_manager.create_texture(view, sampler).unwrap();
+
+    (mesh, texture)
+}
+
+/// Compares `actual` with the reference image `tests/golden/<name>.png`.
+/// The reference is written instead when `UPDATE_GOLDEN` is set, a missing
+/// reference is an error otherwise.
+pub fn assert_golden(name: &str, actual: &RgbaImage, tolerance: Tolerance) {
+    let path = golden_path(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        actual.save(&path).unwrap();
+        eprintln!("Wrote golden image {}", path.display());
+        return;
+    }
+    if !path.exists() {
+        let dir = output_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let actual_path = dir.join(format!("{}.actual.png", name));
+        actual.save(&actual_path).unwrap();
+        panic!(
+            "{}: no golden image at {}, check {} and rerun with UPDATE_GOLDEN=1 to accept it",
+            name,
+            path.display(),
+            actual_path.display()
+        );
+    }
+
+    let expected = image::open(&path).unwrap().to_rgba8();
+    let comparison = compare(actual, &expected, tolerance).unwrap_or_else(|| {
+        panic!(
+            "{}: rendered frame is {:?} but the golden image is {:?}",
+            name,
+            actual.dimensions(),
+            expected.dimensions()
+        )
+    });
+
+    if !comparison.passes(tolerance) {
+        let dir = output_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let actual_path = dir.join(format!("{}.actual.png", name));
+        let diff_path = dir.join(format!("{}.diff.png", name));
+        actual.save(&actual_path).unwrap();
+        comparison.diff.save(&diff_path).unwrap();
+
+        panic!(
+            "{}: {} pixels differ from the golden image (max channel delta {}), see {} and {}",
+            name,
+            comparison.mismatched_pixels,
+            comparison.max_channel_delta,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use cgmath::{InnerSpace, Vector3};
+use image::{Rgba, RgbaImage};
+use wgpu_renderer::ressource_manager::cubemap::{
+    equirectangular_to_cubemap, equirectangular_uv, CubeFace,
+};
+use wgpu_renderer::ressource_manager::format::TextureImage;
+
+#[test]
+fn face_centers_point_along_their_axis() {
+    let centers = CubeFace::ALL.map(|face| face.direction(0.0, 0.0));
+    assert_eq!(centers[0], Vector3::unit_x());
+    assert_eq!(centers[1], -Vector3::unit_x());
+    assert_eq!(centers[2], Vector3::unit_y());
+    assert_eq!(centers[3], -Vector3::unit_y());
+    assert_eq!(centers[4], Vector3::unit_z());
+    assert_eq!(centers[5], -Vector3::unit_z());
+
+    // The top of the side faces looks up.
+    assert!(CubeFace::PositiveX.direction(0.0, -1.0).y > 0.0);
+    assert!((CubeFace::NegativeZ.direction(1.0, 1.0).magnitude() - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn panorama_center_looks_toward_negative_z() {
+    let [u, v] = equirectangular_uv(-Vector3::unit_z());
+    assert!((u - 0.5).abs() < 1e-6 && (v - 0.5).abs() < 1e-6);
+
+    let [_, v] = equirectangular_uv(Vector3::unit_y());
+    assert!(v.abs() < 1e-6);
+}
+
+#[test]
+fn panorama_is_projected_on_each_face() {
+    // Top half red, bottom half blue.
+    let panorama = RgbaImage::from_fn(8, 4, |_, y| {
+        if y < 2 {
+            Rgba([255, 0, 0, 255])
+        } else {
+            Rgba([0, 0, 255, 255])
+        }
+    });
+    let faces = equirectangular_to_cubemap(&TextureImage::Ldr(panorama), 4);
+
+    let pixel = |face: CubeFace, x: u32, y: u32| match &faces[face as usize] {
+        TextureImage::Hdr(image) => image.get_pixel(x, y).0,
+        TextureImage::Ldr(_) => unreachable!(),
+    };
+    assert_eq!(pixel(CubeFace::PositiveY, 1, 1), [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(pixel(CubeFace::NegativeY, 2, 2), [0.0, 0.0, 1.0, 1.0]);
+    assert_eq!(faces[0].dimensions(), (4, 4));
+}
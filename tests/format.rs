@@ -0,0 +1,261 @@
+use wgpu_renderer::ressource_manager::format::{
+    f16_to_f32, f32_to_f16, texels_to_rgba, HdrImage, TextureFormat, TextureImage,
+};
+
+use image::{Rgb, Rgba, RgbaImage};
+
+#[test]
+fn half_floats_are_rounded_to_nearest() {
+    assert_eq!(f32_to_f16(0.0), 0x0000);
+    assert_eq!(f32_to_f16(-0.0), 0x8000);
+    assert_eq!(f32_to_f16(1.0), 0x3c00);
+    assert_eq!(f32_to_f16(-2.0), 0xc000);
+    assert_eq!(f32_to_f16(65504.0), 0x7bff);
+    assert_eq!(f32_to_f16(1.0e6), 0x7c00);
+    assert_eq!(f32_to_f16(f32::INFINITY), 0x7c00);
+    assert_eq!(f32_to_f16(f32::NAN) & 0x7e00, 0x7e00);
+    // Smallest subnormal half.
+    assert_eq!(f32_to_f16(5.960_464_5e-8), 0x0001);
+    assert_eq!(f32_to_f16(1.0e-10), 0x0000);
+}
+
+#[test]
+fn images_are_converted_to_the_texel_layout() {
+    let image = TextureImage::Ldr(RgbaImage::from_pixel(2, 1, Rgba([255, 128, 0, 255])));
+
+    assert_eq!(image.to_bytes(TextureFormat::Rgba8Srgb).len(), 8);
+    assert_eq!(image.to_bytes(TextureFormat::Rg8), vec![255, 128, 255, 128]);
+    assert_eq!(image.to_bytes(TextureFormat::R8).len(), 2);
+
+    let floats = image.to_bytes(TextureFormat::Rgba32Float);
+    assert_eq!(floats.len(), 2 * 16);
+    assert_eq!(floats[0..4], 1.0f32.to_le_bytes());
+
+    let halves = image.to_bytes(TextureFormat::Rgba16Float);
+    assert_eq!(halves.len(), 2 * 8);
+    assert_eq!(halves[0..2], 0x3c00u16.to_le_bytes());
+}
+
+#[test]
+fn hdr_files_keep_their_range() {
+    let mut bytes = Vec::new();
+    image::hdr::HdrEncoder::new(&mut bytes)
+        .encode(&[Rgb([4.0, 0.5, 0.0]); 4], 2, 2)
+        .unwrap();
+
+    let image = TextureImage::decode(&bytes).unwrap();
+    let hdr: &HdrImage = match &image {
+        TextureImage::Hdr(hdr) => hdr,
+        TextureImage::Ldr(_) => panic!("decoded as an 8 bits image"),
+    };
+    assert_eq!(hdr.dimensions(), (2, 2));
+    assert_eq!(hdr.get_pixel(1, 1).0, [4.0, 0.5, 0.0, 1.0]);
+
+    // Clamped when stored in 8 bits.
+    assert_eq!(
+        image.to_bytes(TextureFormat::Rgba8Linear)[0..4],
+        [255, 128, 0, 255]
+    );
+}
+
+#[test]
+fn half_floats_convert_back_exactly() {
+    for value in [
+        0.0,
+        -0.0,
+        1.0,
+        -2.5,
+        65504.0,
+        0.000_061_035_156,
+        5.960_464_5e-8,
+        0.333_251_95,
+    ] {
+        assert_eq!(f16_to_f32(f32_to_f16(value)), value);
+    }
+    assert!(f16_to_f32(0x7e00).is_nan());
+    assert_eq!(f16_to_f32(0xfc00), f32::NEG_INFINITY);
+}
+
+#[test]
+fn texels_are_read_back_as_rgba() {
+    let image = RgbaImage::from_fn(3, 2, |x, y| Rgba([x as u8 * 100, y as u8 * 200, 50, 255]));
+    let ldr = TextureImage::Ldr(image.clone());
+
+    for format in [
+        TextureFormat::Rgba8Srgb,
+        TextureFormat::Rgba16Float,
+        TextureFormat::Rgba32Float,
+    ] {
+        assert_eq!(texels_to_rgba(format, (3, 2), &ldr.to_bytes(format)), image);
+    }
+
+    let rg = texels_to_rgba(
+        TextureFormat::Rg8,
+        (3, 2),
+        &ldr.to_bytes(TextureFormat::Rg8),
+    );
+    assert_eq!(rg.get_pixel(2, 1), &Rgba([200, 200, 0, 255]));
+}
+
+/// Writes a single part scanline EXR file, `lines` holding the raw values of each line.
+fn exr(channels: &[(&str, i32)], compression: u8, size: (i32, i32), lines: &[Vec<u8>]) -> Vec<u8> {
+    let attribute = |bytes: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]| {
+        for string in &[name, kind] {
+            bytes.extend_from_slice(string.as_bytes());
+            bytes.push(0);
+        }
+        bytes.extend_from_slice(&(value.len() as i32).to_le_bytes());
+        bytes.extend_from_slice(value);
+    };
+
+    let mut bytes = vec![0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0];
+    let mut list = Vec::new();
+    for (name, pixel_type) in channels {
+        list.extend_from_slice(name.as_bytes());
+        list.push(0);
+        list.extend_from_slice(&pixel_type.to_le_bytes());
+        list.extend_from_slice(&[0, 0, 0, 0]);
+        list.extend_from_slice(&1i32.to_le_bytes());
+        list.extend_from_slice(&1i32.to_le_bytes());
+    }
+    list.push(0);
+    attribute(&mut bytes, "channels", "chlist", &list);
+    attribute(&mut bytes, "compression", "compression", &[compression]);
+    let window: Vec<u8> = [0, 0, size.0 - 1, size.1 - 1]
+        .iter()
+        .flat_map(|value: &i32| value.to_le_bytes())
+        .collect();
+    attribute(&mut bytes, "dataWindow", "box2i", &window);
+    bytes.push(0);
+
+    // One line per chunk, except for zip compression which uses 16.
+    let lines_per_chunk = if compression == 3 { 16 } else { 1 };
+    let chunks: Vec<Vec<u8>> = lines
+        .chunks(lines_per_chunk)
+        .enumerate()
+        .map(|(i, lines)| {
+            let data = lines.concat();
+            let data = if compression == 0 {
+                data
+            } else {
+                // Even bytes then odd bytes, delta encoded.
+                let mut predicted: Vec<u8> = data
+                    .iter()
+                    .step_by(2)
+                    .chain(data.iter().skip(1).step_by(2))
+                    .copied()
+                    .collect();
+                for i in (1..predicted.len()).rev() {
+                    predicted[i] = predicted[i]
+                        .wrapping_sub(predicted[i - 1])
+                        .wrapping_add(128);
+                }
+                let compressed = miniz_oxide::deflate::compress_to_vec_zlib(&predicted, 6);
+                // Like OpenEXR, chunks that don't shrink are stored as is.
+                if compressed.len() < data.len() {
+                    compressed
+                } else {
+                    data
+                }
+            };
+            let mut chunk = ((i * lines_per_chunk) as i32).to_le_bytes().to_vec();
+            chunk.extend_from_slice(&(data.len() as i32).to_le_bytes());
+            chunk.extend_from_slice(&data);
+            chunk
+        })
+        .collect();
+
+    let mut offset = bytes.len() + 8 * chunks.len();
+    for chunk in &chunks {
+        bytes.extend_from_slice(&(offset as u64).to_le_bytes());
+        offset += chunk.len();
+    }
+    bytes.extend(chunks.concat());
+    bytes
+}
+
+fn halves(values: &[f32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| f32_to_f16(*value).to_le_bytes())
+        .collect()
+}
+
+#[test]
+fn exr_files_keep_their_range() {
+    // Channels are stored in alphabetical order.
+    let channels = [("A", 2), ("B", 1), ("G", 1), ("R", 1)];
+    let line = |red: f32| {
+        let mut line: Vec<u8> = [0.5f32, 0.25]
+            .iter()
+            .flat_map(|a| a.to_le_bytes())
+            .collect();
+        line.extend(halves(&[0.0, 1.0]));
+        line.extend(halves(&[2.0, 0.0]));
+        line.extend(halves(&[red, 8.0]));
+        line
+    };
+    let bytes = exr(&channels, 0, (2, 2), &[line(4.0), line(16.0)]);
+
+    let image = match TextureImage::decode(&bytes).unwrap() {
+        TextureImage::Hdr(image) => image,
+        TextureImage::Ldr(_) => panic!("EXR files are decoded as floats"),
+    };
+    assert_eq!(image.dimensions(), (2, 2));
+    assert_eq!(image.get_pixel(0, 0).0, [4.0, 2.0, 0.0, 0.5]);
+    assert_eq!(image.get_pixel(1, 0).0, [8.0, 0.0, 1.0, 0.25]);
+    assert_eq!(image.get_pixel(0, 1).0, [16.0, 2.0, 0.0, 0.5]);
+}
+
+#[test]
+fn zip_compressed_exr_files_are_decoded() {
+    // 20 lines span two zip chunks, the last one partially filled.
+    let lines: Vec<Vec<u8>> = (0..20)
+        .map(|y| {
+            let mut values = vec![100.0; 32];
+            values[0] = y as f32;
+            values[1] = y as f32 + 0.5;
+            halves(&values)
+        })
+        .collect();
+    let bytes = exr(&[("Y", 1)], 3, (32, 20), &lines);
+    // Smaller than the raw data, the chunks are compressed.
+    assert!(bytes.len() < 20 * 64);
+
+    let image = match TextureImage::decode(&bytes).unwrap() {
+        TextureImage::Hdr(image) => image,
+        TextureImage::Ldr(_) => panic!("EXR files are decoded as floats"),
+    };
+    assert_eq!(image.dimensions(), (32, 20));
+    assert_eq!(image.get_pixel(1, 17).0, [17.5, 17.5, 17.5, 1.0]);
+    assert_eq!(image.get_pixel(2, 19).0, [100.0, 100.0, 100.0, 1.0]);
+}
+
+#[test]
+fn unsupported_exr_files_are_errors() {
+    let mut tiled = exr(&[("Y", 1)], 0, (1, 1), &[halves(&[1.0])]);
+    tiled[5] = 0x02;
+    assert!(matches!(
+        TextureImage::decode(&tiled),
+        Err(wgpu_renderer::Error::Exr(_))
+    ));
+
+    let piz = exr(&[("Y", 1)], 4, (1, 1), &[halves(&[1.0])]);
+    assert!(matches!(
+        TextureImage::decode(&piz),
+        Err(wgpu_renderer::Error::Exr(_))
+    ));
+
+    let truncated = exr(&[("Y", 1)], 0, (1, 1), &[halves(&[1.0])]);
+    assert!(matches!(
+        TextureImage::decode(&truncated[..truncated.len() - 1]),
+        Err(wgpu_renderer::Error::Exr(_))
+    ));
+
+    // Rejected before anything is allocated for the pixels.
+    let huge = exr(&[("Y", 1)], 0, (i32::MAX, 1), &[]);
+    assert!(matches!(
+        TextureImage::decode(&huge),
+        Err(wgpu_renderer::Error::Exr("data window too large"))
+    ));
+}
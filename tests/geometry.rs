@@ -0,0 +1,142 @@
+use wgpu_renderer::geometry::{
+    check_vertex_layout, ColoredVertex, Instance, LitVertex, Vertex, VertexLayout, COLOR_LOCATION,
+    INSTANCE_LOCATIONS, UV_LOCATION,
+};
+use wgpu_renderer::Error;
+
+fn check_layout<V: VertexLayout>() {
+    let descriptor = V::BUFFER_DESCRIPTOR;
+    assert_eq!(descriptor.stride, std::mem::size_of::<V>() as u64);
+    assert_eq!(descriptor.step_mode, wgpu::InputStepMode::Vertex);
+
+    let mut end = 0;
+    for attribute in descriptor.attributes {
+        assert!(!INSTANCE_LOCATIONS.contains(&attribute.shader_location));
+        assert!(attribute.offset >= end);
+        end = attribute.offset + attribute.format.size();
+    }
+    assert_eq!(end, descriptor.stride);
+}
+
+#[test]
+fn instances_use_the_instance_locations() {
+    let locations: Vec<u32> = Instance::BUFFER_DESCRIPTOR
+        .attributes
+        .iter()
+        .map(|attribute| attribute.shader_location)
+        .collect();
+    assert_eq!(locations, INSTANCE_LOCATIONS.collect::<Vec<_>>());
+}
+
+#[test]
+fn vertex_layouts_are_packed() {
+    check_layout::<Vertex>();
+    check_layout::<ColoredVertex>();
+    check_layout::<LitVertex>();
+}
+
+#[test]
+fn lit_vertex_defaults() {
+    let vertex = LitVertex::new([0.0; 3], [0.25, 0.75], [0.0, 0.0, 1.0]);
+
+    assert_eq!(vertex.color, [1.0; 4]);
+    assert_eq!(vertex.uv1, vertex.uv);
+    assert_eq!(vertex.tangent, [1.0, 0.0, 0.0, 1.0]);
+}
+
+// `align` checks that other representation hints are accepted next to `C`.
+#[repr(C, align(4))]
+#[derive(Clone, Copy, VertexLayout)]
+struct SkinnedVertex {
+    position: [f32; 3],
+    uv: [f32; 2],
+    normal: [f32; 3],
+    #[vertex(location = 10)]
+    color: [u8; 4],
+    #[vertex(location = 12)]
+    joints: [u16; 4],
+    weights: [f32; 4],
+}
+
+#[test]
+fn derived_layout() {
+    check_layout::<SkinnedVertex>();
+    check_vertex_layout(&SkinnedVertex::BUFFER_DESCRIPTOR).unwrap();
+
+    let attributes = SkinnedVertex::BUFFER_DESCRIPTOR.attributes;
+    let locations: Vec<u32> = attributes.iter().map(|a| a.shader_location).collect();
+    assert_eq!(locations, [0, 1, 8, 10, 12, 13]);
+    let formats: Vec<wgpu::VertexFormat> = attributes.iter().map(|a| a.format).collect();
+    assert_eq!(
+        formats,
+        [
+            wgpu::VertexFormat::Float3,
+            wgpu::VertexFormat::Float2,
+            wgpu::VertexFormat::Float3,
+            wgpu::VertexFormat::Uchar4Norm,
+            wgpu::VertexFormat::Ushort4,
+            wgpu::VertexFormat::Float4,
+        ]
+    );
+}
+
+fn attribute(shader_location: u32, format: wgpu::VertexFormat) -> wgpu::VertexAttributeDescriptor {
+    wgpu::VertexAttributeDescriptor {
+        offset: 0,
+        shader_location,
+        format,
+    }
+}
+
+fn check_attributes(attributes: &[wgpu::VertexAttributeDescriptor]) -> Result<(), Error> {
+    check_vertex_layout(&wgpu::VertexBufferDescriptor {
+        stride: 64,
+        step_mode: wgpu::InputStepMode::Vertex,
+        attributes,
+    })
+}
+
+#[test]
+fn vertex_layouts_are_checked_against_the_shaders() {
+    use wgpu::VertexFormat::*;
+
+    check_vertex_layout(&Vertex::BUFFER_DESCRIPTOR).unwrap();
+    check_vertex_layout(&ColoredVertex::BUFFER_DESCRIPTOR).unwrap();
+    check_vertex_layout(&LitVertex::BUFFER_DESCRIPTOR).unwrap();
+
+    assert!(matches!(
+        check_attributes(&[attribute(0, Float3), attribute(10, Float4)]),
+        Err(Error::MissingVertexAttribute(UV_LOCATION))
+    ));
+    // Lit vertices are read with their color.
+    assert!(matches!(
+        check_attributes(&[
+            attribute(0, Float3),
+            attribute(1, Float2),
+            attribute(8, Float3)
+        ]),
+        Err(Error::MissingVertexAttribute(COLOR_LOCATION))
+    ));
+    assert!(matches!(
+        check_attributes(&[attribute(0, Float3), attribute(1, Uchar4Norm)]),
+        Err(Error::VertexAttributeFormat {
+            location: UV_LOCATION,
+            format: Uchar4Norm
+        })
+    ));
+    assert!(matches!(
+        check_attributes(&[
+            attribute(0, Float3),
+            attribute(1, Float2),
+            attribute(6, Float4)
+        ]),
+        Err(Error::InstanceLocation(6))
+    ));
+    // Locations the shaders don't read can have any format.
+    check_attributes(&[
+        attribute(0, Float3),
+        attribute(1, Float2),
+        attribute(12, Uint4),
+    ])
+    .unwrap();
+}
@@ -0,0 +1,242 @@
+mod common;
+
+use cgmath::{Deg, Matrix4, Point3, SquareMatrix, Vector3};
+use gltf::image::{Data, Format};
+use gltf::mesh::Mode;
+use image::{Rgba, RgbaImage};
+use wgpu_renderer::camera::Camera;
+use wgpu_renderer::ressource_manager::gltf::{
+    image_to_rgba, triangle_list, GltfMaterial, GltfMesh, GltfNode, GltfPrimitive, GltfScene,
+};
+use wgpu_renderer::scene::Scene;
+
+const QUAD: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/assets/quad.gltf");
+const LINES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/assets/lines.gltf");
+
+fn translation(x: f32) -> Matrix4<f32> {
+    Matrix4::from_translation(Vector3::new(x, 0.0, 0.0))
+}
+
+/// root (mesh 0) -> child (no mesh) -> grandchild (mesh 0), plus an orphan node.
+fn hierarchy() -> GltfScene {
+    let node = |transform, children, mesh| GltfNode {
+        name: None,
+        transform,
+        children,
+        mesh,
+    };
+
+    GltfScene {
+        nodes: vec![
+            node(translation(1.0), vec![1], Some(0)),
+            node(translation(2.0), vec![2], None),
+            node(translation(4.0), vec![], Some(0)),
+            node(translation(8.0), vec![], Some(0)),
+        ],
+        roots: vec![0],
+        meshes: vec![GltfMesh {
+            name: None,
+            primitives: vec![
+                GltfPrimitive {
+                    mesh: common::mesh(10),
+                    material: Some(0),
+                },
+                GltfPrimitive {
+                    mesh: common::mesh(11),
+                    material: None,
+                },
+            ],
+        }],
+        materials: vec![GltfMaterial {
+            name: None,
+            base_color_factor: [1.0; 4],
+            base_color_texture: Some(common::texture(20)),
+        }],
+    }
+}
+
+#[test]
+fn world_transforms_follow_the_hierarchy() {
+    let transforms = hierarchy().world_transforms(Matrix4::identity());
+
+    assert_eq!(
+        transforms,
+        vec![
+            Some(translation(1.0)),
+            Some(translation(3.0)),
+            Some(translation(7.0)),
+            None,
+        ]
+    );
+}
+
+#[test]
+fn submit_draws_every_reachable_primitive() {
+    let mut scene = Scene::new();
+    hierarchy().submit(&mut scene, translation(100.0), common::texture(30));
+
+    let items: Vec<_> = scene
+        .items()
+        .iter()
+        .map(|item| (item.mesh, item.texture, item.transform))
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            (common::mesh(10), common::texture(20), translation(101.0)),
+            (common::mesh(11), common::texture(30), translation(101.0)),
+            (common::mesh(10), common::texture(20), translation(107.0)),
+            (common::mesh(11), common::texture(30), translation(107.0)),
+        ]
+    );
+}
+
+#[test]
+fn images_are_converted_to_rgba() {
+    let rgb = Data {
+        pixels: vec![1, 2, 3, 4, 5, 6],
+        format: Format::R8G8B8,
+        width: 2,
+        height: 1,
+    };
+    assert_eq!(
+        image_to_rgba(&rgb).into_raw(),
+        vec![1, 2, 3, 255, 4, 5, 6, 255]
+    );
+
+    let bgra = Data {
+        pixels: vec![1, 2, 3, 4],
+        format: Format::B8G8R8A8,
+        width: 1,
+        height: 1,
+    };
+    assert_eq!(image_to_rgba(&bgra).into_raw(), vec![3, 2, 1, 4]);
+
+    let luma16 = Data {
+        pixels: 0xabcdu16.to_ne_bytes().to_vec(),
+        format: Format::R16,
+        width: 1,
+        height: 1,
+    };
+    assert_eq!(
+        image_to_rgba(&luma16).into_raw(),
+        vec![0xab, 0xab, 0xab, 255]
+    );
+}
+
+#[test]
+fn strips_and_fans_are_converted_to_triangle_lists() {
+    let indices = [0, 1, 2, 3, 4];
+    assert_eq!(
+        triangle_list(Mode::Triangles, &indices[..3]),
+        Some(vec![0, 1, 2])
+    );
+    assert_eq!(
+        triangle_list(Mode::TriangleStrip, &indices),
+        Some(vec![0, 1, 2, 2, 1, 3, 2, 3, 4])
+    );
+    assert_eq!(
+        triangle_list(Mode::TriangleFan, &indices),
+        Some(vec![0, 1, 2, 0, 2, 3, 0, 3, 4])
+    );
+    assert_eq!(
+        triangle_list(Mode::TriangleStrip, &indices[..2]),
+        Some(vec![])
+    );
+    assert_eq!(triangle_list(Mode::LineStrip, &indices), None);
+}
+
+#[test]
+fn fixtures_are_valid() {
+    let (document, _, _) = gltf::import(QUAD).unwrap();
+    let primitive = document
+        .meshes()
+        .next()
+        .unwrap()
+        .primitives()
+        .next()
+        .unwrap();
+    assert_eq!(primitive.mode(), Mode::TriangleStrip);
+
+    let (document, _, _) = gltf::import(LINES).unwrap();
+    let primitive = document
+        .meshes()
+        .next()
+        .unwrap()
+        .primitives()
+        .next()
+        .unwrap();
+    assert_eq!(primitive.mode(), Mode::LineStrip);
+}
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn load_gltf_uploads_the_hierarchy() {
+    let mut renderer = common::headless_renderer(16, 16);
+    let scene = renderer.ressource_manager_mut().load_gltf(QUAD).unwrap();
+
+    assert_eq!(scene.roots, vec![0]);
+    assert_eq!(scene.nodes[0].children, vec![1]);
+    assert_eq!(scene.nodes[1].mesh, Some(0));
+    assert_eq!(scene.meshes[0].name.as_deref(), Some("quad"));
+    assert_eq!(scene.meshes[0].primitives[0].material, Some(0));
+    assert_eq!(scene.materials[0].base_color_factor, [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(scene.materials[0].base_color_texture, None);
+
+    let transforms = scene.world_transforms(Matrix4::identity());
+    assert_eq!(
+        transforms[1],
+        Some(Matrix4::from_translation(Vector3::new(0.0, 0.0, -2.0)))
+    );
+}
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn base_color_factors_tint_the_vertices() {
+    let mut renderer = common::headless_renderer(16, 16);
+    let ressource_manager = renderer.ressource_manager_mut();
+    let scene = ressource_manager.load_gltf(QUAD).unwrap();
+    let texture_manager = &mut ressource_manager.texture_manager;
+    let view = texture_manager.create_texture_view_from_image(&RgbaImage::from_pixel(
+        1,
+        1,
+        Rgba([255; 4]),
+    ));
+    let sampler = texture_manager.create_sampler(&wgpu::SamplerDescriptor::default());
+    let white = texture_manager.create_texture(view, sampler).unwrap();
+
+    renderer.scene_mut().camera = Camera::perspective(
+        Point3::new(0.0, 0.0, 1.0),
+        Point3::new(0.0, 0.0, 0.0),
+        Deg(45.0),
+    );
+    scene.submit(renderer.scene_mut(), Matrix4::identity(), white);
+    renderer.render().unwrap();
+
+    // The material is red and has no texture.
+    let [r, g, b, _] = renderer.read_frame().unwrap().unwrap().get_pixel(8, 8).0;
+    assert!(r > 0 && g == 0 && b == 0, "{:?}", [r, g, b]);
+}
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn load_gltf_rejects_lines() {
+    let mut renderer = common::headless_renderer(16, 16);
+    let err = renderer
+        .ressource_manager_mut()
+        .load_gltf(LINES)
+        .unwrap_err();
+
+    assert!(
+        matches!(
+            err,
+            wgpu_renderer::Error::UnsupportedPrimitiveMode {
+                mesh: 0,
+                primitive: 0,
+                mode: Mode::LineStrip,
+            }
+        ),
+        "{:?}",
+        err
+    );
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::Tolerance;
+
+fn happy_tree() -> image::RgbaImage {
+    image::open(concat!(env!("CARGO_MANIFEST_DIR"), "/happy-tree.png"))
+        .unwrap()
+        .to_rgba8()
+}
+
+#[test]
+fn identical_images_match() {
+    let image = happy_tree();
+    let comparison = common::compare(&image, &image, Tolerance::default()).unwrap();
+
+    assert_eq!(comparison.mismatched_pixels, 0);
+    assert_eq!(comparison.max_channel_delta, 0);
+    assert!(comparison.passes(Tolerance::default()));
+}
+
+#[test]
+fn changed_pixels_are_reported() {
+    let expected = happy_tree();
+    let mut actual = expected.clone();
+    actual.get_pixel_mut(0, 0).0[0] ^= 0xff;
+    let pixel = actual.get_pixel_mut(1, 0);
+    pixel.0[1] = pixel.0[1].wrapping_add(1);
+
+    let comparison = common::compare(&actual, &expected, Tolerance::default()).unwrap();
+    assert_eq!(comparison.mismatched_pixels, 1);
+    assert_eq!(comparison.max_channel_delta, 0xff);
+    assert_eq!(comparison.diff.get_pixel(0, 0).0, [255, 0, 0, 255]);
+    assert!(!comparison.passes(Tolerance::default()));
+
+    let loose = Tolerance {
+        channel: 2,
+        pixels: 1.0,
+    };
+    assert!(comparison.passes(loose));
+}
+
+#[test]
+fn different_sizes_do_not_compare() {
+    let expected = happy_tree();
+    let actual = image::RgbaImage::new(1, 1);
+
+    assert!(common::compare(&actual, &expected, Tolerance::default()).is_none());
+}
@@ -0,0 +1,75 @@
+use wgpu_renderer::ressource_manager::handle::{Resource, SlotMap};
+use wgpu_renderer::{Error, HandleKind};
+
+#[derive(Debug, PartialEq)]
+struct Dummy(u32);
+
+impl Resource for Dummy {
+    const KIND: HandleKind = HandleKind::Mesh;
+}
+
+#[test]
+fn inserted_values_can_be_retrieved() {
+    let mut slots = SlotMap::new();
+    let a = slots.insert(Dummy(1));
+    let b = slots.insert(Dummy(2));
+
+    assert_ne!(a, b);
+    assert_eq!(slots.get(a).unwrap(), &Dummy(1));
+    assert_eq!(slots.get(b).unwrap(), &Dummy(2));
+    assert_eq!(slots.len(), 2);
+}
+
+#[test]
+fn use_after_drop_is_detected() {
+    let mut slots = SlotMap::new();
+    let a = slots.insert(Dummy(1));
+    assert_eq!(slots.release(a).unwrap(), Some(Dummy(1)));
+
+    // The slot is reused, but the old handle stays invalid.
+    let b = slots.insert(Dummy(2));
+    assert_eq!(a.index(), b.index());
+    assert_ne!(a.generation(), b.generation());
+
+    assert!(matches!(
+        slots.get(a),
+        Err(Error::StaleHandle {
+            kind: HandleKind::Mesh,
+            ..
+        })
+    ));
+    assert!(matches!(slots.release(a), Err(Error::StaleHandle { .. })));
+    assert_eq!(slots.get(b).unwrap(), &Dummy(2));
+    assert_eq!(slots.len(), 1);
+}
+
+#[test]
+fn unknown_handles_are_invalid() {
+    let mut other = SlotMap::new();
+    for i in 0..4 {
+        other.insert(Dummy(i));
+    }
+    let foreign = other.insert(Dummy(4));
+
+    let slots: SlotMap<Dummy> = SlotMap::new();
+    assert!(matches!(
+        slots.get(foreign),
+        Err(Error::InvalidHandle { index: 4, .. })
+    ));
+    assert!(!slots.contains(foreign));
+}
+
+#[test]
+fn values_are_freed_by_the_last_release() {
+    let mut slots = SlotMap::new();
+    let a = slots.insert(Dummy(1));
+    assert_eq!(slots.retain(a).unwrap(), a);
+    assert_eq!(slots.ref_count(a).unwrap(), 2);
+
+    assert_eq!(slots.release(a).unwrap(), None);
+    assert_eq!(slots.get(a).unwrap(), &Dummy(1));
+    assert_eq!(slots.release(a).unwrap(), Some(Dummy(1)));
+
+    assert!(!slots.contains(a));
+    assert!(matches!(slots.retain(a), Err(Error::StaleHandle { .. })));
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use wgpu_renderer::Vertex;
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn u32_indices_reach_vertices_past_u16() {
+    let mut renderer = common::headless_renderer(64, 64);
+
+    let (quad, texture) = common::textured_quad(renderer.ressource_manager_mut());
+    renderer.scene_mut().draw(quad, texture);
+    renderer.render().unwrap();
+    let expected = renderer.read_frame().unwrap().unwrap();
+
+    // The same quad, stored after 70000 unused vertices.
+    let mut vertices = vec![Vertex::new([0.0, 0.0, 0.0], [0.0, 0.0]); 70_000];
+    vertices.extend_from_slice(&[
+        Vertex::new([-0.5, -0.5, 0.0], [0.0, 1.0]),
+        Vertex::new([0.5, -0.5, 0.0], [1.0, 1.0]),
+        Vertex::new([0.5, 0.5, 0.0], [1.0, 0.0]),
+        Vertex::new([-0.5, 0.5, 0.0], [0.0, 0.0]),
+    ]);
+    let indices: Vec<u32> = [0, 1, 2, 0, 2, 3].iter().map(|i| 70_000 + i).collect();
+    let mesh_manager = &mut renderer.ressource_manager_mut().mesh_manager;
+    let far_quad = mesh_manager
+        .create_mesh_indexed(&vertices, &indices)
+        .unwrap();
+    assert_eq!(
+        mesh_manager.index_format(far_quad).unwrap(),
+        Some(wgpu::IndexFormat::Uint32)
+    );
+    assert_eq!(
+        mesh_manager.index_format(quad).unwrap(),
+        Some(wgpu::IndexFormat::Uint16)
+    );
+
+    renderer.scene_mut().draw(far_quad, texture);
+    renderer.render().unwrap();
+    let frame = renderer.read_frame().unwrap().unwrap();
+
+    assert!(
+        frame == expected,
+        "the u32 indexed quad is drawn differently"
+    );
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use image::{Rgba, RgbaImage};
+use wgpu_renderer::ressource_manager::format::{TextureFormat, TextureImage};
+use wgpu_renderer::ressource_manager::mipmap::{mip_level_count, mip_level_size};
+use wgpu_renderer::Error;
+
+#[test]
+fn full_chain_goes_down_to_one_pixel() {
+    assert_eq!(mip_level_count(1, 1), 1);
+    assert_eq!(mip_level_count(256, 256), 9);
+    assert_eq!(mip_level_count(300, 20), 9);
+    assert_eq!(mip_level_count(20, 512), 10);
+}
+
+#[test]
+fn level_sizes_are_halved_and_clamped() {
+    assert_eq!(mip_level_size(300, 20, 0), (300, 20));
+    assert_eq!(mip_level_size(300, 20, 1), (150, 10));
+    assert_eq!(mip_level_size(300, 20, 5), (9, 1));
+    assert_eq!(mip_level_size(300, 20, 8), (1, 1));
+}
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn generated_levels_average_the_previous_one() {
+    let mut renderer = common::headless_renderer(16, 16);
+    let texture_manager = &mut renderer.ressource_manager_mut().texture_manager;
+
+    let mut image = RgbaImage::new(2, 2);
+    image.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+    image.put_pixel(1, 0, Rgba([0, 255, 0, 255]));
+    image.put_pixel(0, 1, Rgba([0, 0, 255, 255]));
+    image.put_pixel(1, 1, Rgba([255, 255, 255, 255]));
+    let view = texture_manager
+        .create_texture_view_from_texture_image(
+            &TextureImage::Ldr(image),
+            TextureFormat::Rgba8Linear,
+        )
+        .unwrap();
+    assert_eq!(
+        texture_manager.texture_view_mip_level_count(view).unwrap(),
+        2
+    );
+
+    let texels = texture_manager
+        .read_texture_view_level_texels(view, 1)
+        .unwrap();
+    assert_eq!(texels.len(), 4);
+    for (channel, expected) in texels.iter().zip(&[128, 128, 128, 255]) {
+        assert!((*channel as i32 - expected).abs() <= 1, "{:?}", texels);
+    }
+
+    let err = texture_manager
+        .read_texture_view_level_texels(view, 2)
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::MipLevelOutOfRange { level: 2, .. }),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn pre_built_levels_are_kept() {
+    let mut renderer = common::headless_renderer(16, 16);
+    let texture_manager = &mut renderer.ressource_manager_mut().texture_manager;
+
+    let level = |size, value| {
+        TextureImage::Ldr(RgbaImage::from_pixel(size, size, Rgba([value, 0, 0, 255])))
+    };
+    let view = texture_manager
+        .create_texture_view_with_mips(
+            &[level(4, 10), level(2, 20), level(1, 30)],
+            TextureFormat::R8,
+        )
+        .unwrap();
+
+    // R8 keeps the luminance of the image.
+    let luminance = |value| {
+        let image = TextureImage::Ldr(RgbaImage::from_pixel(1, 1, Rgba([value, 0, 0, 255])));
+        image.to_bytes(TextureFormat::R8)[0]
+    };
+    assert_eq!(
+        texture_manager
+            .read_texture_view_level_texels(view, 2)
+            .unwrap(),
+        vec![luminance(30)]
+    );
+
+    let err = texture_manager
+        .create_texture_view_with_mips(
+            &[level(2, 0), level(1, 0), level(1, 0)],
+            TextureFormat::Rgba8Srgb,
+        )
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::TooManyMipLevels { count: 3, max: 2 }),
+        "{:?}",
+        err
+    );
+}
@@ -0,0 +1,66 @@
+use cgmath::{InnerSpace, Vector3};
+use wgpu_renderer::ressource_manager::obj::parse_obj;
+use wgpu_renderer::LitVertex;
+
+const SHAPES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/assets/shapes.obj");
+
+#[test]
+fn parses_every_object() {
+    let (meshes, materials) = parse_obj(SHAPES).unwrap();
+
+    let names: Vec<_> = meshes.iter().map(|mesh| mesh.name.as_str()).collect();
+    assert_eq!(names, ["quad", "triangle"]);
+
+    let quad = &meshes[0];
+    assert_eq!(quad.positions.len(), 4);
+    assert_eq!(quad.uvs.len(), 4);
+    assert_eq!(quad.normals, vec![[0.0, 0.0, 1.0]; 4]);
+    // The quad face is triangulated.
+    assert_eq!(quad.indices.len(), 6);
+
+    let triangle = &meshes[1];
+    assert_eq!(triangle.positions.len(), 3);
+    assert!(triangle.uvs.is_empty());
+    assert!(triangle.normals.is_empty());
+
+    assert_eq!(materials.len(), 2);
+    let tree = &materials[quad.material.unwrap()];
+    assert_eq!(tree.name, "tree");
+    assert_eq!(tree.diffuse_texture, "../../happy-tree.png");
+    assert_eq!(
+        materials[triangle.material.unwrap()].diffuse,
+        [1.0, 0.0, 0.0]
+    );
+}
+
+#[test]
+fn texture_coordinates_are_flipped() {
+    let (meshes, _) = parse_obj(SHAPES).unwrap();
+    let vertices = meshes[0].vertices();
+
+    assert_eq!(
+        vertices[0],
+        LitVertex::new([-1.0, -1.0, 0.0], [0.0, 1.0], [0.0, 0.0, 1.0])
+    );
+    assert_eq!(
+        vertices[2],
+        LitVertex::new([1.0, 1.0, 0.0], [1.0, 0.0], [0.0, 0.0, 1.0])
+    );
+}
+
+#[test]
+fn missing_normals_are_computed_from_the_faces() {
+    let (meshes, _) = parse_obj(SHAPES).unwrap();
+    let triangle = &meshes[1];
+    let normals: Vec<_> = triangle.vertices().iter().map(|v| v.normal).collect();
+
+    let [a, b, c] = [0, 1, 2].map(|i| Vector3::from(triangle.positions[i]));
+    let expected: [f32; 3] = (b - a).cross(c - a).normalize().into();
+    assert_eq!(normals, vec![expected; 3]);
+}
+
+#[test]
+fn missing_file_is_an_error() {
+    let err = parse_obj("does/not/exist.obj").unwrap_err();
+    assert!(matches!(err, wgpu_renderer::Error::Obj(_)), "{:?}", err);
+}
@@ -0,0 +1,106 @@
+use cgmath::{InnerSpace, Vector3};
+use wgpu_renderer::geometry::primitives::{self, MeshData};
+
+/// Checks the indices, that normals and tangents are unit vectors orthogonal to each
+/// other, and that triangles face the side of their normals.
+fn check_mesh(data: &MeshData, extent: Vector3<f32>) {
+    assert!(!data.indices.is_empty());
+    assert_eq!(data.indices.len() % 3, 0);
+    assert!(data
+        .indices
+        .iter()
+        .all(|&index| (index as usize) < data.vertices.len()));
+
+    for vertex in &data.vertices {
+        let normal = Vector3::from(vertex.normal);
+        let tangent = Vector3::new(vertex.tangent[0], vertex.tangent[1], vertex.tangent[2]);
+        assert!((normal.magnitude() - 1.0).abs() < 1e-4);
+        assert!((tangent.magnitude() - 1.0).abs() < 1e-4);
+        assert!(normal.dot(tangent).abs() < 1e-4);
+        for axis in 0..3 {
+            assert!(vertex.position[axis].abs() <= extent[axis] + 1e-4);
+        }
+    }
+
+    for triangle in data.indices.chunks(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| data.vertices[triangle[i] as usize]);
+        let (pa, pb, pc) = (
+            Vector3::from(a.position),
+            Vector3::from(b.position),
+            Vector3::from(c.position),
+        );
+        let face_normal = (pb - pa).cross(pc - pa);
+        // Triangles at the poles of the spheres are flat.
+        if face_normal.magnitude() < 1e-6 {
+            continue;
+        }
+        let normal = Vector3::from(a.normal) + Vector3::from(b.normal) + Vector3::from(c.normal);
+        assert!(face_normal.dot(normal) > 0.0, "{:?}", triangle);
+
+        // The texture isn't mirrored: u grows along the tangent.
+        let du = (b.uv[0] - a.uv[0], c.uv[0] - a.uv[0]);
+        let tangent = Vector3::new(a.tangent[0], a.tangent[1], a.tangent[2]);
+        let along = ((pb - pa).dot(tangent), (pc - pa).dot(tangent));
+        assert!(du.0 * along.0 + du.1 * along.1 >= -1e-4, "{:?}", triangle);
+    }
+}
+
+fn unit() -> Vector3<f32> {
+    Vector3::new(0.5, 0.5, 0.5)
+}
+
+#[test]
+fn plane() {
+    let data = primitives::plane(4, 2);
+    assert_eq!(data.vertices.len(), 5 * 3);
+    assert_eq!(data.indices.len(), 4 * 2 * 6);
+    check_mesh(&data, Vector3::new(0.5, 0.0, 0.5));
+}
+
+#[test]
+fn cube() {
+    let data = primitives::cube(2);
+    assert_eq!(data.vertices.len(), 6 * 9);
+    assert_eq!(data.indices.len(), 6 * 4 * 6);
+    check_mesh(&data, unit());
+}
+
+#[test]
+fn spheres() {
+    check_mesh(&primitives::uv_sphere(16, 8), unit());
+
+    let icosphere = primitives::icosphere(2);
+    assert_eq!(icosphere.indices.len(), 20 * 16 * 3);
+    check_mesh(&icosphere, unit());
+    for vertex in &icosphere.vertices {
+        assert!((Vector3::from(vertex.position).magnitude() - 0.5).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn cylinder_and_cone() {
+    check_mesh(&primitives::cylinder(12, 3), unit());
+    check_mesh(&primitives::cone(12, 3), unit());
+}
+
+#[test]
+fn capsule_and_torus() {
+    check_mesh(
+        &primitives::capsule(0.25, 1.0, 12, 4),
+        Vector3::new(0.25, 0.5, 0.25),
+    );
+    check_mesh(
+        &primitives::torus(0.35, 0.15, 16, 8),
+        Vector3::new(0.5, 0.15, 0.5),
+    );
+}
+
+#[test]
+fn subdivisions_are_clamped() {
+    check_mesh(&primitives::cube(0), unit());
+    check_mesh(&primitives::uv_sphere(0, 0), unit());
+    check_mesh(
+        &primitives::torus(0.35, 0.15, 0, 0),
+        Vector3::new(0.5, 0.15, 0.5),
+    );
+}
@@ -0,0 +1,192 @@
+mod common;
+
+use image::{Rgba, RgbaImage};
+use wgpu_renderer::ressource_manager::format::{TextureFormat, TextureImage};
+use wgpu_renderer::ressource_manager::texture::TextureId;
+use wgpu_renderer::ressource_manager::RessourceManager;
+use wgpu_renderer::scene::Scene;
+use wgpu_renderer::{Error, LitVertex};
+
+fn white_texture(ressource_manager: &mut RessourceManager) -> TextureId {
+    let texture_manager = &mut ressource_manager.texture_manager;
+    let view = texture_manager.create_texture_view_from_image(&RgbaImage::from_pixel(
+        1,
+        1,
+        Rgba([255; 4]),
+    ));
+    let sampler = texture_manager.create_sampler(&wgpu::SamplerDescriptor::default());
+    texture_manager.create_texture(view, sampler).unwrap()
+}
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn render_targets_take_any_color_format() {
+    let mut renderer = common::headless_renderer(64, 64);
+    let (quad, _) = common::textured_quad(renderer.ressource_manager_mut());
+    let white = white_texture(renderer.ressource_manager_mut());
+
+    let texture_manager = &mut renderer.ressource_manager_mut().texture_manager;
+    let target = texture_manager.create_render_target(64, 64, TextureFormat::Rgba16Float);
+    let mut scene = Scene::new();
+    scene.draw(quad, white);
+    renderer.render_to_texture(target, &scene).unwrap();
+
+    let texture_manager = &renderer.ressource_manager().texture_manager;
+    let image = texture_manager.read_texture_view(target).unwrap();
+    assert_eq!(image.get_pixel(32, 32).0, [255; 4]);
+    assert_eq!(image.get_pixel(0, 0).0, [0, 0, 0, 255]);
+}
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn depth_targets_keep_the_depth_of_the_scene() {
+    let mut renderer = common::headless_renderer(64, 64);
+    let (quad, _) = common::textured_quad(renderer.ressource_manager_mut());
+    let white = white_texture(renderer.ressource_manager_mut());
+
+    let texture_manager = &mut renderer.ressource_manager_mut().texture_manager;
+    let target = texture_manager.create_render_target(64, 64, TextureFormat::Rgba8Srgb);
+    let depth_target = texture_manager.create_depth_target(64, 64);
+    let mut scene = Scene::new();
+    scene.draw(quad, white);
+    renderer
+        .render_to_texture_with_depth(target, depth_target, &scene)
+        .unwrap();
+
+    let texture_manager = &renderer.ressource_manager().texture_manager;
+    let depth: Vec<f32> = texture_manager
+        .read_texture_view_texels(depth_target)
+        .unwrap()
+        .chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect();
+    // Cleared to 1.0 around the quad.
+    assert_eq!(depth[0], 1.0);
+    assert!(depth[32 * 64 + 32] < 1.0, "{}", depth[32 * 64 + 32]);
+}
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn render_target_formats_and_sizes_are_checked() {
+    let mut renderer = common::headless_renderer(64, 64);
+    let texture_manager = &mut renderer.ressource_manager_mut().texture_manager;
+    let target = texture_manager.create_render_target(64, 64, TextureFormat::Rgba8Srgb);
+    let depth_target = texture_manager.create_depth_target(32, 32);
+    let mipmapped = texture_manager.create_texture_view_from_image(&RgbaImage::new(4, 4));
+    let scene = Scene::new();
+
+    assert!(matches!(
+        renderer.render_to_texture(depth_target, &scene),
+        Err(Error::RenderTargetFormat(TextureFormat::Depth32Float))
+    ));
+    assert!(matches!(
+        renderer.render_to_texture(mipmapped, &scene),
+        Err(Error::RenderTargetMipLevels(3))
+    ));
+    assert!(matches!(
+        renderer.render_to_texture_with_depth(target, target, &scene),
+        Err(Error::RenderTargetFormat(TextureFormat::Rgba8Srgb))
+    ));
+    assert!(matches!(
+        renderer.render_to_texture_with_depth(target, depth_target, &scene),
+        Err(Error::RenderTargetSize {
+            expected: (64, 64),
+            found: (32, 32)
+        })
+    ));
+}
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn texels_cannot_be_uploaded_to_depth_textures() {
+    let mut renderer = common::headless_renderer(64, 64);
+    let texture_manager = &mut renderer.ressource_manager_mut().texture_manager;
+    let depth_target = texture_manager.create_depth_target(2, 2);
+    let image = TextureImage::Ldr(RgbaImage::new(2, 2));
+
+    assert!(matches!(
+        texture_manager.write_texture_view(depth_target, (0, 0), (2, 2), &[0; 16]),
+        Err(Error::TextureUploadFormat(TextureFormat::Depth32Float))
+    ));
+    assert!(matches!(
+        texture_manager.create_texture_view_from_texture_image(&image, TextureFormat::Depth32Float),
+        Err(Error::TextureUploadFormat(TextureFormat::Depth32Float))
+    ));
+    assert!(matches!(
+        texture_manager.create_texture_view_with_mips(
+            std::slice::from_ref(&image),
+            TextureFormat::Depth32Float
+        ),
+        Err(Error::TextureUploadFormat(TextureFormat::Depth32Float))
+    ));
+}
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn nested_texture_passes_are_rendered_first() {
+    let mut renderer = common::headless_renderer(64, 64);
+    let (quad, _) = common::textured_quad(renderer.ressource_manager_mut());
+    let white = white_texture(renderer.ressource_manager_mut());
+
+    let texture_manager = &mut renderer.ressource_manager_mut().texture_manager;
+    let inner_target = texture_manager.create_render_target(64, 64, TextureFormat::Rgba8Srgb);
+    let sampler = texture_manager.create_sampler(&wgpu::SamplerDescriptor::default());
+    let inner_texture = texture_manager
+        .create_texture(inner_target, sampler)
+        .unwrap();
+    let outer_target = texture_manager.create_render_target(64, 64, TextureFormat::Rgba8Srgb);
+
+    // The outer pass draws the result of the inner one, which is only submitted to it.
+    let mut inner = Scene::new();
+    inner.draw(quad, white);
+    let mut outer = Scene::new();
+    outer.render_to_texture(inner_target, inner);
+    outer.draw(quad, inner_texture);
+    renderer.scene_mut().render_to_texture(outer_target, outer);
+    renderer.render().unwrap();
+
+    let texture_manager = &renderer.ressource_manager().texture_manager;
+    let image = texture_manager.read_texture_view(outer_target).unwrap();
+    assert_eq!(image.get_pixel(32, 32).0, [255; 4]);
+}
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn vertices_with_normals_are_lit() {
+    let mut renderer = common::headless_renderer(64, 64);
+    let white = white_texture(renderer.ressource_manager_mut());
+
+    // Shade of a white quad facing the camera with the given normal.
+    let mut shade = |normal: [f32; 3]| {
+        let ressource_manager = renderer.ressource_manager_mut();
+        let vertices = &[
+            LitVertex::new([-0.5, -0.5, 0.0], [0.0, 1.0], normal),
+            LitVertex::new([0.5, -0.5, 0.0], [1.0, 1.0], normal),
+            LitVertex::new([0.5, 0.5, 0.0], [1.0, 0.0], normal),
+            LitVertex::new([-0.5, 0.5, 0.0], [0.0, 0.0], normal),
+        ];
+        let quad = ressource_manager
+            .mesh_manager
+            .create_mesh_indexed(vertices, &[0u16, 1, 2, 0, 2, 3])
+            .unwrap();
+        let texture_manager = &mut ressource_manager.texture_manager;
+        let target = texture_manager.create_render_target(64, 64, TextureFormat::Rgba8Linear);
+        let mut scene = Scene::new();
+        scene.draw(quad, white);
+        renderer.render_to_texture(target, &scene).unwrap();
+
+        let texture_manager = &renderer.ressource_manager().texture_manager;
+        texture_manager
+            .read_texture_view(target)
+            .unwrap()
+            .get_pixel(32, 32)
+            .0
+    };
+
+    // Ambient plus the diffuse light coming from the front, ambient alone from the back.
+    let front = shade([0.0, 0.0, 1.0]);
+    let back = shade([0.0, 0.0, -1.0]);
+    assert!((159..=163).contains(&front[0]), "{:?}", front);
+    assert!((49..=53).contains(&back[0]), "{:?}", back);
+    assert_eq!(front[3], 255);
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use cgmath::{Deg, Point3, Vector3};
+use image::{Rgba, RgbaImage};
+use wgpu_renderer::camera::Camera;
+use wgpu_renderer::scene::Transform;
+use wgpu_renderer::{DepthSettings, HandleKind, Instance, StencilSettings};
+use winit::dpi::PhysicalSize;
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn resize_recreates_the_frame_and_depth_buffer() {
+    let mut renderer = common::headless_renderer(64, 64);
+    let (mesh, texture) = common::textured_quad(renderer.ressource_manager_mut());
+
+    renderer.resize(PhysicalSize::new(96, 32));
+    renderer.scene_mut().draw(mesh, texture);
+    renderer.render().unwrap();
+    assert_eq!(
+        renderer.read_frame().unwrap().unwrap().dimensions(),
+        (96, 32)
+    );
+
+    // A minimized window keeps the last buffers.
+    renderer.resize(PhysicalSize::new(0, 0));
+    renderer.scene_mut().draw(mesh, texture);
+    renderer.render().unwrap();
+    assert_eq!(
+        renderer.read_frame().unwrap().unwrap().dimensions(),
+        (96, 32)
+    );
+}
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn depth_settings_recreate_the_depth_buffer() {
+    let mut renderer = common::headless_renderer(64, 64);
+    let (mesh, texture) = common::textured_quad(renderer.ressource_manager_mut());
+
+    renderer.set_depth_settings(DepthSettings {
+        stencil: Some(StencilSettings {
+            state: wgpu::StencilStateDescriptor::default(),
+            reference: 1,
+            clear_stencil: 0,
+        }),
+        ..DepthSettings::default()
+    });
+    renderer.resize(PhysicalSize::new(32, 32));
+    renderer.scene_mut().draw(mesh, texture);
+    renderer.render().unwrap();
+    assert_eq!(
+        renderer.read_frame().unwrap().unwrap().dimensions(),
+        (32, 32)
+    );
+}
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn replaced_cameras_get_the_aspect_ratio_of_the_frame() {
+    let mut renderer = common::headless_renderer(128, 64);
+
+    renderer.scene_mut().camera = Camera::perspective(
+        Point3::new(0.0, 0.0, 2.0),
+        Point3::new(0.0, 0.0, 0.0),
+        Deg(45.0),
+    );
+    renderer.render().unwrap();
+    assert_eq!(renderer.scene_mut().camera.aspect, 2.0);
+}
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn nearer_geometry_hides_farther_geometry() {
+    let mut renderer = common::headless_renderer(64, 64);
+    let (quad, _) = common::textured_quad(renderer.ressource_manager_mut());
+    let texture_manager = &mut renderer.ressource_manager_mut().texture_manager;
+    let view = texture_manager.create_texture_view_from_image(&RgbaImage::from_pixel(
+        1,
+        1,
+        Rgba([255; 4]),
+    ));
+    let sampler = texture_manager.create_sampler(&wgpu::SamplerDescriptor::default());
+    let white = texture_manager.create_texture(view, sampler).unwrap();
+
+    let near = Instance::new(
+        Transform::from_translation(Vector3::new(0.0, 0.0, 0.5)),
+        [1.0, 0.0, 0.0, 1.0],
+    );
+    let far = Instance::new(
+        Transform::from_translation(Vector3::new(0.0, 0.0, -0.5)),
+        [0.0, 0.0, 1.0, 1.0],
+    );
+    // Whatever the drawing order, the nearer red quad is the one visible.
+    for instances in &[[far, near], [near, far]] {
+        for instance in instances {
+            renderer
+                .scene_mut()
+                .draw_instanced(quad, white, std::slice::from_ref(instance));
+        }
+        renderer.render().unwrap();
+        let frame = renderer.read_frame().unwrap().unwrap();
+        assert_eq!(frame.get_pixel(32, 32).0, [255, 0, 0, 255]);
+    }
+}
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn dropped_ressources_fail_the_frame() {
+    let mut renderer = common::headless_renderer(64, 64);
+    let (mesh, texture) = common::textured_quad(renderer.ressource_manager_mut());
+
+    renderer.scene_mut().draw(mesh, texture);
+    let texture_manager = &mut renderer.ressource_manager_mut().texture_manager;
+    texture_manager.drop_texture(texture).unwrap();
+    let err = renderer.render().unwrap_err();
+    assert!(
+        matches!(
+            err,
+            wgpu_renderer::Error::StaleHandle {
+                kind: HandleKind::Texture,
+                ..
+            }
+        ),
+        "{:?}",
+        err
+    );
+
+    // The scene was emptied, the next frame renders.
+    renderer.render().unwrap();
+}
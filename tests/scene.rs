@@ -0,0 +1,86 @@
+mod common;
+
+use cgmath::{Deg, Matrix4, Quaternion, Rotation3, SquareMatrix, Vector3, Vector4};
+use wgpu_renderer::scene::{Scene, Transform};
+use wgpu_renderer::Instance;
+
+fn assert_close(actual: Vector4<f32>, expected: Vector4<f32>) {
+    let delta = actual - expected;
+    assert!(
+        delta.x.abs() < 1e-5 && delta.y.abs() < 1e-5 && delta.z.abs() < 1e-5,
+        "{:?} != {:?}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn default_transform_is_identity() {
+    assert_eq!(Transform::default().matrix(), Matrix4::identity());
+}
+
+#[test]
+fn transform_scales_then_rotates_then_translates() {
+    let transform = Transform::from_translation(Vector3::new(1.0, 2.0, 3.0))
+        .with_rotation(Quaternion::from_angle_z(Deg(90.0)))
+        .with_scale(Vector3::new(2.0, 2.0, 2.0));
+
+    let point = transform.matrix() * Vector4::new(1.0, 0.0, 0.0, 1.0);
+    assert_close(point, Vector4::new(1.0, 4.0, 3.0, 1.0));
+}
+
+#[test]
+fn same_mesh_can_be_drawn_many_times() {
+    let mut scene = Scene::new();
+    for i in 0..3 {
+        scene.draw_transformed(
+            common::mesh(1),
+            common::texture(1),
+            Transform::from_translation(Vector3::new(i as f32, 0.0, 0.0)),
+        );
+    }
+
+    assert_eq!(scene.items().len(), 3);
+    assert_eq!(
+        scene.items()[2].transform,
+        Matrix4::from_translation(Vector3::new(2.0, 0.0, 0.0))
+    );
+
+    scene.clear();
+    assert!(scene.items().is_empty());
+}
+
+#[test]
+fn instances_are_grouped_per_item() {
+    let mut scene = Scene::new();
+    scene.draw(common::mesh(1), common::texture(1));
+    let instances: Vec<_> = (0..1000)
+        .map(|i| {
+            Instance::new(
+                Transform::from_translation(Vector3::new(i as f32, 0.0, 0.0)),
+                [1.0, 0.0, 0.0, 1.0],
+            )
+        })
+        .collect();
+    scene.draw_instanced(common::mesh(2), common::texture(1), &instances);
+    scene.draw(common::mesh(3), common::texture(1));
+
+    let ranges: Vec<_> = scene
+        .instanced_items()
+        .map(|(item, range)| (item.mesh, range))
+        .collect();
+    assert_eq!(
+        ranges,
+        vec![
+            (common::mesh(1), 0..1),
+            (common::mesh(2), 1..1001),
+            (common::mesh(3), 1001..1002)
+        ]
+    );
+    assert_eq!(scene.instances().len(), 1002);
+    assert_eq!(scene.instances()[0], Instance::default());
+
+    scene.clear();
+    assert!(scene.instances().is_empty());
+    assert_eq!(scene.instanced_items().count(), 0);
+}
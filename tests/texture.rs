@@ -0,0 +1,89 @@
+mod common;
+
+use wgpu_renderer::ressource_manager::format::{TextureFormat, TextureImage};
+use wgpu_renderer::Error;
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn texture_regions_are_checked() {
+    let mut renderer = common::headless_renderer(16, 16);
+    let texture_manager = &mut renderer.ressource_manager_mut().texture_manager;
+
+    let view = texture_manager.create_empty_texture_view(8, 4, TextureFormat::Rg8);
+    assert_eq!(texture_manager.texture_view_size(view).unwrap(), (8, 4));
+
+    texture_manager
+        .write_texture_view(view, (6, 2), (2, 2), &[0; 2 * 2 * 2])
+        .unwrap();
+    assert!(matches!(
+        texture_manager.write_texture_view(view, (7, 0), (2, 1), &[0; 4]),
+        Err(Error::RegionOutOfBounds { .. })
+    ));
+    // The end of the region overflows.
+    assert!(matches!(
+        texture_manager.write_texture_view(view, (u32::MAX, 0), (2, 1), &[0; 4]),
+        Err(Error::RegionOutOfBounds { .. })
+    ));
+    assert!(matches!(
+        texture_manager.write_texture_view(view, (0, 0), (2, 2), &[0; 16]),
+        Err(Error::TexelDataSize {
+            expected: 8,
+            found: 16
+        })
+    ));
+}
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn written_texels_are_read_back() {
+    let mut renderer = common::headless_renderer(16, 16);
+    let texture_manager = &mut renderer.ressource_manager_mut().texture_manager;
+
+    // 300 pixels wide rows are not aligned on 256 bytes.
+    let view = texture_manager.create_empty_texture_view(300, 2, TextureFormat::Rgba8Linear);
+    let texels: Vec<u8> = (0..300 * 2 * 4).map(|i| (i % 251) as u8).collect();
+    texture_manager
+        .write_texture_view(view, (0, 0), (300, 2), &texels)
+        .unwrap();
+
+    assert_eq!(
+        texture_manager.read_texture_view_texels(view).unwrap(),
+        texels
+    );
+    assert_eq!(
+        texture_manager.read_texture_view(view).unwrap().into_raw(),
+        texels
+    );
+}
+
+#[test]
+#[ignore = "needs a gpu adapter"]
+fn mipmaps_are_regenerated_on_demand() {
+    let mut renderer = common::headless_renderer(16, 16);
+    let texture_manager = &mut renderer.ressource_manager_mut().texture_manager;
+
+    let black = image::RgbaImage::from_pixel(2, 2, image::Rgba([0, 0, 0, 255]));
+    let view = texture_manager
+        .create_texture_view_from_texture_image(
+            &TextureImage::Ldr(black),
+            TextureFormat::Rgba8Linear,
+        )
+        .unwrap();
+    texture_manager
+        .write_texture_view(view, (0, 0), (2, 2), &[255; 2 * 2 * 4])
+        .unwrap();
+    assert_eq!(
+        texture_manager
+            .read_texture_view_level_texels(view, 1)
+            .unwrap(),
+        vec![0, 0, 0, 255]
+    );
+
+    texture_manager.generate_mipmaps(view).unwrap();
+    assert_eq!(
+        texture_manager
+            .read_texture_view_level_texels(view, 1)
+            .unwrap(),
+        vec![255; 4]
+    );
+}